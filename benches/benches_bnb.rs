@@ -11,48 +11,56 @@ fn benchmark_select_coin_bnb(c: &mut Criterion) {
             weight: 500,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 400,
             weight: 200,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 40000,
             weight: 300,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 25000,
             weight: 100,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 35000,
             weight: 150,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 600,
             weight: 250,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 30000,
             weight: 120,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 5000,
             weight: 50,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
     ];
 
@@ -68,6 +76,8 @@ fn benchmark_select_coin_bnb(c: &mut Criterion) {
         avg_output_weight: 10,
         min_change_value: 500,
         excess_strategy: ExcessStrategy::ToChange,
+        must_spend: vec![],
+        excluded: vec![],
     };
 
     let mut final_result: Option<Result<SelectionOutput, SelectionError>> = None;
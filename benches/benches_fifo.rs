@@ -11,18 +11,21 @@ fn benchmark_select_coin_fifo(c: &mut Criterion) {
             weight: 100,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 2000,
             weight: 200,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 3000,
             weight: 300,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
     ];
 
@@ -38,6 +41,8 @@ fn benchmark_select_coin_fifo(c: &mut Criterion) {
         avg_output_weight: 10,
         min_change_value: 500,
         excess_strategy: ExcessStrategy::ToChange,
+        must_spend: vec![],
+        excluded: vec![],
     };
 
     c.bench_function("select_coin_fifo", |b| {
@@ -12,13 +12,14 @@ fn knapsack_setup_output_groups(
     target_feerate: f32,
 ) -> Vec<OutputGroup> {
     let mut inputs: Vec<OutputGroup> = Vec::new();
-    for (i, j) in value.into_iter().zip(weights.into_iter()) {
+    for (i, j) in value.into_iter().zip(weights) {
         let k = i.saturating_add((j as f32 * target_feerate).ceil() as u64);
         inputs.push(OutputGroup {
             value: k,
             weight: j as u64,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         })
     }
     inputs
@@ -57,6 +58,8 @@ fn benchmark_select_coin_knapsack(c: &mut Criterion) {
             avg_output_weight: 10,
             min_change_value,
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         }
     };
 
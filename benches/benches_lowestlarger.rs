@@ -11,72 +11,84 @@ fn benchmark_select_coin_lowestlarger(c: &mut Criterion) {
             weight: 100,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 1500,
             weight: 200,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 3400,
             weight: 300,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 2200,
             weight: 150,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 1190,
             weight: 200,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 3300,
             weight: 100,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 1000,
             weight: 190,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 2000,
             weight: 210,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 3000,
             weight: 300,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 2250,
             weight: 250,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 190,
             weight: 220,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
         OutputGroup {
             value: 1750,
             weight: 170,
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         },
     ];
 
@@ -92,6 +104,8 @@ fn benchmark_select_coin_lowestlarger(c: &mut Criterion) {
         avg_output_weight: 10,
         min_change_value: 500,
         excess_strategy: ExcessStrategy::ToChange,
+        must_spend: vec![],
+        excluded: vec![],
     };
 
     let mut final_result: Option<Result<SelectionOutput, SelectionError>> = None;
@@ -10,6 +10,7 @@ use bitcoin::{
     Transaction, TxIn, TxOut, Txid, Witness,
 };
 use rust_coinselect::{
+    bitcoin::ScriptType,
     selectcoin::select_coin,
     types::{CoinSelectionOpt, ExcessStrategy, OutputGroup},
     utils::{calculate_base_weight_btc, calculate_fee},
@@ -106,14 +107,22 @@ fn main() {
     // Prepare CoinSelectionOpt
     let long_term_feerate = 10.0;
     let change_weight = change_output.weight().to_wu();
-    let change_cost = calculate_fee(change_weight, long_term_feerate);
+    let change_cost = calculate_fee(change_weight, long_term_feerate).unwrap();
     let target_weight = target_output.weight().to_wu();
     let avg_output_weight = (change_weight + target_weight) / 2;
-    let avg_input_weight = inputs
+    // Script types of the outputs spent by the inputs, which determine the weight of their satisfaction.
+    // The weight of already signed inputs would underestimate the fee when a signature is shorter than the largest one.
+    let script_types = [
+        ScriptType::P2pkh,
+        ScriptType::P2pkh,
+        ScriptType::P2wpkh,
+        ScriptType::P2wpkh,
+    ];
+    let avg_input_weight = script_types
         .iter()
-        .map(|input| input.segwit_weight().to_wu())
+        .map(|script_type| script_type.input_weight())
         .sum::<u64>()
-        / inputs.len() as u64;
+        / script_types.len() as u64;
 
     // Create coin selection options
     let coin_selection_option = CoinSelectionOpt {
@@ -121,29 +130,32 @@ fn main() {
         target_feerate: 15.0,
         long_term_feerate: Some(long_term_feerate),
         min_absolute_fee: 4000,
-        base_weight: calculate_base_weight_btc(target_weight + change_weight),
+        // The change output weight is accounted for separately through change_weight
+        base_weight: calculate_base_weight_btc(target_weight),
         change_weight,
         change_cost,
         avg_input_weight,
         avg_output_weight,
         min_change_value: 100,
         excess_strategy: ExcessStrategy::ToChange,
+        must_spend: vec![],
+        excluded: vec![],
     };
 
     // Mock values for each input
     let mock_input_values = vec![100_000, 3_000_000, 1_000_000, 500_000];
 
     // Create OutputGroups from each input
-    let utxos: Vec<OutputGroup> = inputs
-        .clone()
+    let utxos: Vec<OutputGroup> = script_types
         .into_iter()
         .zip(mock_input_values)
-        .map(|(input, value)| OutputGroup {
+        .map(|(script_type, value)| OutputGroup {
             // In practice, the details about the UTXO, used as input, is obtained from the UTXO set maintained by a node.
             value,
-            weight: input.segwit_weight().to_wu(),
+            weight: script_type.input_weight(),
             input_count: 1,
             creation_sequence: None,
+            ..Default::default()
         })
         .collect();
 
@@ -168,34 +180,23 @@ fn main() {
             println!("The selected OutputGroups are......");
             log_utxos(&selected_output_groups);
 
-            let selected_txins_value: u64 = selection
-                .selected_inputs
-                .iter()
-                .map(|&i| utxos[i].value)
-                .sum();
+            // The selection output reports the fee paid at the target feerate and the change, if any
+            let mut outputs = vec![target_output];
+            if let Some(change_value) = selection.change_value {
+                change_output.value = Amount::from_sat(change_value);
+                outputs.push(change_output);
+            }
+            println!(
+                "Target value = {}. Change value = {:?}, fee = {}, total input value of tx = {}",
+                target, selection.change_value, selection.fee, selection.total_value
+            );
 
-            // Create a transaction for the purpose of calculating the fee using the selected inputs
-            let mut tx = Transaction {
+            let tx = Transaction {
                 version: transaction::Version::TWO,
                 lock_time: LockTime::ZERO,
                 input: selected_txins,
-                output: vec![target_output, change_output.clone()],
+                output: outputs,
             };
-            let fee = calculate_fee(
-                tx.weight().to_wu(),
-                coin_selection_option.long_term_feerate.unwrap(),
-            );
-
-            // update the change output with the actual change value
-            let change_value = selected_txins_value - (target + fee);
-            change_output.value = Amount::from_sat(change_value);
-            println!(
-                "Target value = {}. Change value = {}, total input value of tx = {}",
-                target, change_value, selected_txins_value
-            );
-
-            // update the change output of the transaction
-            tx.output[1] = change_output;
 
             println!("The final transaction id = {}", tx.compute_txid());
             println!("Now the below tx can be broadcasted");
@@ -0,0 +1,325 @@
+#ifndef RUST_COINSELECT_H
+#define RUST_COINSELECT_H
+
+/* Generated by cbindgen from src/capi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * See [`ExcessStrategy`], the values of [`CoinselectOptions::excess_strategy`].
+ */
+typedef enum CoinselectExcessStrategy {
+  /**
+   * See [`ExcessStrategy::ToFee`].
+   */
+  COINSELECT_EXCESS_STRATEGY_TO_FEE = 0,
+  /**
+   * See [`ExcessStrategy::ToRecipient`].
+   */
+  COINSELECT_EXCESS_STRATEGY_TO_RECIPIENT = 1,
+  /**
+   * See [`ExcessStrategy::ToChange`].
+   */
+  COINSELECT_EXCESS_STRATEGY_TO_CHANGE = 2,
+} CoinselectExcessStrategy;
+
+/**
+ * Status returned by every function, `COINSELECT_STATUS_OK` on success.
+ *
+ * The codes are stable: new errors get new codes, existing codes are never reused.
+ */
+typedef enum CoinselectStatus {
+  /**
+   * The selection succeeded.
+   */
+  COINSELECT_STATUS_OK = 0,
+  /**
+   * See [`SelectionError::NonPositiveFeeRate`].
+   */
+  COINSELECT_STATUS_NON_POSITIVE_FEE_RATE = 1,
+  /**
+   * See [`SelectionError::AbnormallyHighFeeRate`].
+   */
+  COINSELECT_STATUS_ABNORMALLY_HIGH_FEE_RATE = 2,
+  /**
+   * See [`SelectionError::InsufficientFunds`].
+   */
+  COINSELECT_STATUS_INSUFFICIENT_FUNDS = 3,
+  /**
+   * See [`SelectionError::NoSolutionFound`].
+   */
+  COINSELECT_STATUS_NO_SOLUTION_FOUND = 4,
+  /**
+   * See [`SelectionError::InsufficientReplacementFee`].
+   */
+  COINSELECT_STATUS_INSUFFICIENT_REPLACEMENT_FEE = 5,
+  /**
+   * See [`SelectionError::MustSpendOutOfRange`].
+   */
+  COINSELECT_STATUS_MUST_SPEND_OUT_OF_RANGE = 6,
+  /**
+   * See [`SelectionError::InputReserved`].
+   */
+  COINSELECT_STATUS_INPUT_RESERVED = 7,
+  /**
+   * A required pointer is null, or an argument isn't one of its allowed values.
+   */
+  COINSELECT_STATUS_INVALID_ARGUMENT = -1,
+  /**
+   * The library panicked, which is a bug. `out` isn't written.
+   */
+  COINSELECT_STATUS_PANIC = -2,
+} CoinselectStatus;
+
+/**
+ * An input candidate, see [`OutputGroup`].
+ */
+typedef struct CoinselectOutputGroup {
+  /**
+   * Total value of the UTXO(s).
+   */
+  uint64_t value;
+  /**
+   * Total weight of spending the UTXO(s).
+   */
+  uint64_t weight;
+  /**
+   * The total number of inputs.
+   */
+  size_t input_count;
+  /**
+   * Relative creation sequence, read only if `has_creation_sequence` is set.
+   */
+  uint32_t creation_sequence;
+  /**
+   * Whether the group has a creation sequence, used only for FIFO selection.
+   */
+  bool has_creation_sequence;
+  /**
+   * Total weight of the unconfirmed ancestors, read only if `has_ancestors` is set.
+   */
+  uint64_t ancestor_weight;
+  /**
+   * Total fee paid by the unconfirmed ancestors, read only if `has_ancestors` is set.
+   */
+  uint64_t ancestor_fee;
+  /**
+   * Whether the group has unconfirmed ancestors, see [`Ancestors`].
+   */
+  bool has_ancestors;
+} CoinselectOutputGroup;
+
+/**
+ * The selection options, see [`CoinSelectionOpt`].
+ *
+ * `must_spend` and `excluded` point to `must_spend_len` and `excluded_len` input indices, and may be null when empty.
+ */
+typedef struct CoinselectOptions {
+  /**
+   * The value to select.
+   */
+  uint64_t target_value;
+  /**
+   * The target feerate in sats per weight unit.
+   */
+  float target_feerate;
+  /**
+   * The long term feerate in sats per weight unit, read only if `has_long_term_feerate` is set.
+   */
+  float long_term_feerate;
+  /**
+   * Whether the long term feerate is set.
+   */
+  bool has_long_term_feerate;
+  /**
+   * Lowest possible transaction fee.
+   */
+  uint64_t min_absolute_fee;
+  /**
+   * Weight of the transaction other than the selected inputs.
+   */
+  uint64_t base_weight;
+  /**
+   * Weight added by a change output.
+   */
+  uint64_t change_weight;
+  /**
+   * Cost of creating and later spending a change output.
+   */
+  uint64_t change_cost;
+  /**
+   * Estimate of the average weight of an input.
+   */
+  uint64_t avg_input_weight;
+  /**
+   * Estimate of the average weight of an output.
+   */
+  uint64_t avg_output_weight;
+  /**
+   * Smallest change value worth creating.
+   */
+  uint64_t min_change_value;
+  /**
+   * Where the excess of the selection goes, one of the [`CoinselectExcessStrategy`] values.
+   *
+   * It is an integer since C doesn't restrict enums to their variants, and other values are rejected as
+   * `COINSELECT_STATUS_INVALID_ARGUMENT`.
+   */
+  uint32_t excess_strategy;
+  /**
+   * Indices of the inputs that must be spent.
+   */
+  const size_t *must_spend;
+  /**
+   * Number of indices in `must_spend`.
+   */
+  size_t must_spend_len;
+  /**
+   * Indices of the inputs that must not be selected.
+   */
+  const size_t *excluded;
+  /**
+   * Number of indices in `excluded`.
+   */
+  size_t excluded_len;
+} CoinselectOptions;
+
+/**
+ * A selection, whose `selected_inputs` buffer is owned by the caller and released with [`coinselect_selection_free`].
+ */
+typedef struct CoinselectSelection {
+  /**
+   * Indices of the selected inputs.
+   */
+  size_t *selected_inputs;
+  /**
+   * Number of indices in `selected_inputs`.
+   */
+  size_t selected_inputs_len;
+  /**
+   * Waste of the selection.
+   */
+  int64_t waste;
+  /**
+   * Fee paid by the transaction.
+   */
+  uint64_t fee;
+  /**
+   * Value of the change output, 0 without change.
+   */
+  uint64_t change_value;
+  /**
+   * Excess left to the fee or the recipient.
+   */
+  uint64_t excess;
+} CoinselectSelection;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Runs every algorithm and writes the selection with the least waste to `out`, see [`select_coin`].
+ *
+ * `out` is only written on success, and its buffer must then be released with [`coinselect_selection_free`].
+ *
+ * # Safety
+ *
+ * `inputs` must point to `inputs_len` groups (it may be null if `inputs_len` is 0), `options` to valid options whose
+ * index arrays hold their stated lengths, and `out` to writable memory for a [`CoinselectSelection`].
+ */
+enum CoinselectStatus coinselect_select_coin(const struct CoinselectOutputGroup *inputs,
+                                             size_t inputs_len,
+                                             const struct CoinselectOptions *options,
+                                             struct CoinselectSelection *out);
+
+/**
+ * Selects with Branch and Bound, see [`coinselect_select_coin`] and [`select_coin_bnb`].
+ *
+ * # Safety
+ *
+ * See [`coinselect_select_coin`].
+ */
+enum CoinselectStatus coinselect_select_coin_bnb(const struct CoinselectOutputGroup *inputs,
+                                                 size_t inputs_len,
+                                                 const struct CoinselectOptions *options,
+                                                 struct CoinselectSelection *out);
+
+/**
+ * Selects with CoinGrinder, see [`coinselect_select_coin`] and [`select_coin_coingrinder`].
+ *
+ * # Safety
+ *
+ * See [`coinselect_select_coin`].
+ */
+enum CoinselectStatus coinselect_select_coin_coingrinder(const struct CoinselectOutputGroup *inputs,
+                                                         size_t inputs_len,
+                                                         const struct CoinselectOptions *options,
+                                                         struct CoinselectSelection *out);
+
+/**
+ * Selects the oldest inputs first, see [`coinselect_select_coin`] and [`select_coin_fifo`].
+ *
+ * # Safety
+ *
+ * See [`coinselect_select_coin`].
+ */
+enum CoinselectStatus coinselect_select_coin_fifo(const struct CoinselectOutputGroup *inputs,
+                                                  size_t inputs_len,
+                                                  const struct CoinselectOptions *options,
+                                                  struct CoinselectSelection *out);
+
+/**
+ * Selects with the Knapsack algorithm, see [`coinselect_select_coin`] and [`select_coin_knapsack`].
+ *
+ * # Safety
+ *
+ * See [`coinselect_select_coin`].
+ */
+enum CoinselectStatus coinselect_select_coin_knapsack(const struct CoinselectOutputGroup *inputs,
+                                                      size_t inputs_len,
+                                                      const struct CoinselectOptions *options,
+                                                      struct CoinselectSelection *out);
+
+/**
+ * Selects the lowest larger input, or else the largest ones, see [`coinselect_select_coin`] and [`select_coin_lowestlarger`].
+ *
+ * # Safety
+ *
+ * See [`coinselect_select_coin`].
+ */
+enum CoinselectStatus coinselect_select_coin_lowestlarger(const struct CoinselectOutputGroup *inputs,
+                                                          size_t inputs_len,
+                                                          const struct CoinselectOptions *options,
+                                                          struct CoinselectSelection *out);
+
+/**
+ * Selects with a single random draw, see [`coinselect_select_coin`] and [`select_coin_srd`].
+ *
+ * # Safety
+ *
+ * See [`coinselect_select_coin`].
+ */
+enum CoinselectStatus coinselect_select_coin_srd(const struct CoinselectOutputGroup *inputs,
+                                                 size_t inputs_len,
+                                                 const struct CoinselectOptions *options,
+                                                 struct CoinselectSelection *out);
+
+/**
+ * Releases the buffer of a selection written by one of the selection functions, and resets it.
+ *
+ * # Safety
+ *
+ * `selection` must be null or point to a selection written by this library, not yet released.
+ */
+void coinselect_selection_free(struct CoinselectSelection *selection);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RUST_COINSELECT_H */
@@ -1,30 +1,51 @@
-use rand::{rngs::ThreadRng, thread_rng, Rng};
-
 use crate::{
+    algorithms::CoinSelectionAlgorithm,
     types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput, WasteMetric},
-    utils::{calculate_fee, calculate_waste, effective_value},
+    utils::{
+        calculate_fee, calculate_waste, create_selection_output, effective_value,
+        select_with_input_constraints,
+    },
 };
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+/// Upper bound on the number of nodes visited by the depth-first search.
+const BNB_TOTAL_TRIES: u32 = 1_000_000;
 
-/// Struct MatchParameters encapsulates target_for_match, match_range, and target_feerate.
+/// Struct MatchParameters encapsulates target_for_match and match_range.
 #[derive(Debug)]
 struct MatchParameters {
     target_for_match: u64,
     match_range: u64,
-    target_feerate: f32,
+}
+
+/// An input candidate prepared for the search: its index in the caller's slice, its effective value
+/// and the difference between its fee at the target feerate and at the long term feerate.
+#[derive(Debug)]
+struct BnbCandidate {
+    index: usize,
+    effective_value: u64,
+    timing_cost: i64,
 }
 
 /// Perform Coinselection via Branch And Bound algorithm.
+///
+/// The search is deterministic: inputs are sorted by descending effective value and explored depth-first,
+/// inclusion branch first, as done in Bitcoin Core. Every changeless solution within the match range is
+/// evaluated and the one with the lowest waste is returned.
+///
+/// Returns `NoSolutionFound` if no changeless solution is found within the try budget.
 pub fn select_coin_bnb(
     inputs: &[OutputGroup],
     options: &CoinSelectionOpt,
 ) -> Result<SelectionOutput, SelectionError> {
-    let mut selected_inputs: Vec<usize> = vec![];
-
-    // Variable is mutable for decrement of bnb_tries for every iteration of fn bnb
-    let mut bnb_tries: u32 = 1_000_000;
-
-    let rng = &mut thread_rng();
+    select_with_input_constraints(inputs, options, "bnb", branch_and_bound)
+}
 
+fn branch_and_bound(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
     let cost_per_input = calculate_fee(options.avg_input_weight, options.target_feerate)?;
     let cost_per_output = calculate_fee(options.avg_output_weight, options.target_feerate)?;
 
@@ -32,22 +53,29 @@ pub fn select_coin_bnb(
         target_for_match: options.target_value
             + calculate_fee(options.base_weight, options.target_feerate)?,
         match_range: cost_per_input + cost_per_output,
-        target_feerate: options.target_feerate,
     };
 
-    let mut sorted_inputs: Vec<(usize, &OutputGroup)> = inputs.iter().enumerate().collect();
-    sorted_inputs.sort_by_key(|(_, input)| std::cmp::Reverse(input.value));
+    let long_term_feerate = options.long_term_feerate.unwrap_or(options.target_feerate);
+    let mut candidates = Vec::with_capacity(inputs.len());
+    for (index, input) in inputs.iter().enumerate() {
+        let effective_value = effective_value(input, options.target_feerate)?;
+        // Inputs that cost more to spend than they are worth can never improve a solution
+        if effective_value == 0 {
+            continue;
+        }
+        let timing_cost = calculate_fee(input.weight, options.target_feerate)? as i64
+            - calculate_fee(input.weight, long_term_feerate)? as i64;
+        candidates.push(BnbCandidate {
+            index,
+            effective_value,
+            timing_cost,
+        });
+    }
+    candidates.sort_by_key(|candidate| Reverse(candidate.effective_value));
+
+    let is_feerate_high = options.target_feerate > long_term_feerate;
 
-    let bnb_selected_coin = bnb(
-        &sorted_inputs,
-        &mut selected_inputs,
-        0,
-        0,
-        &mut bnb_tries,
-        rng,
-        &match_parameters,
-    );
-    match bnb_selected_coin {
+    match bnb(&candidates, &match_parameters, is_feerate_high) {
         Some(selected_coin) => {
             let accumulated_value: u64 = selected_coin
                 .iter()
@@ -55,121 +83,118 @@ pub fn select_coin_bnb(
             let accumulated_weight: u64 = selected_coin
                 .iter()
                 .fold(0, |acc, &i| acc + inputs[i].weight);
-            let estimated_fee = 0;
+            let estimated_fee = calculate_fee(accumulated_weight, options.target_feerate)?;
             let waste = calculate_waste(
                 options,
                 accumulated_value,
                 accumulated_weight,
                 estimated_fee,
             );
-            let selection_output = SelectionOutput {
-                selected_inputs: selected_coin,
-                waste: WasteMetric(waste),
-            };
-            Ok(selection_output)
+            create_selection_output(inputs, options, selected_coin, WasteMetric(waste), "bnb")
         }
         None => Err(SelectionError::NoSolutionFound),
     }
 }
 
-/// Return empty vec if no solutions are found
+/// Depth-first search over the candidates sorted in descending order of effective value.
 ///
-/// changing the selected_inputs : &[usize] -> &mut Vec<usize>
+/// Returns the indices of the lowest waste selection found, or `None` if no selection lands in the match range.
 fn bnb(
-    inputs_in_desc_value: &[(usize, &OutputGroup)],
-    selected_inputs: &mut Vec<usize>,
-    acc_eff_value: u64,
-    depth: usize,
-    bnb_tries: &mut u32,
-    rng: &mut ThreadRng,
+    candidates: &[BnbCandidate],
     match_parameters: &MatchParameters,
+    is_feerate_high: bool,
 ) -> Option<Vec<usize>> {
-    if acc_eff_value > match_parameters.target_for_match + match_parameters.match_range {
-        return None;
-    }
-    if acc_eff_value >= match_parameters.target_for_match {
-        return Some(selected_inputs.to_vec());
-    }
+    let target = match_parameters.target_for_match;
+    let upper_bound = target + match_parameters.match_range;
 
-    // Capping the number of iterations on the computation
-    if *bnb_tries == 0 || depth >= inputs_in_desc_value.len() {
-        return None;
-    }
+    // Positions (in `candidates`) of the inputs on the current branch
+    let mut current_selection: Vec<usize> = Vec::new();
+    let mut current_value: u64 = 0;
+    let mut current_waste: i64 = 0;
+    // Sum of the effective values not yet explored on the current branch, used for lookahead pruning
+    let mut current_available: u64 = candidates.iter().map(|c| c.effective_value).sum();
 
-    // Decrement of bnb_tries for every iteration
-    *bnb_tries -= 1;
+    let mut best_selection: Option<Vec<usize>> = None;
+    let mut best_waste = i64::MAX;
 
-    if rng.gen_bool(0.5) {
-        // exploring the inclusion branch
-        // first include then omit
-        let new_effective_value = acc_eff_value
-            + effective_value(
-                inputs_in_desc_value[depth].1,
-                match_parameters.target_feerate,
-            )
-            .unwrap_or_default();
-        selected_inputs.push(inputs_in_desc_value[depth].0);
-        let with_this = bnb(
-            inputs_in_desc_value,
-            selected_inputs,
-            new_effective_value,
-            depth + 1,
-            bnb_tries,
-            rng,
-            match_parameters,
-        );
-        match with_this {
-            Some(_) => with_this,
-            None => {
-                selected_inputs.pop(); // popping out the selected utxo if it does not fit
-                bnb(
-                    inputs_in_desc_value,
-                    selected_inputs,
-                    acc_eff_value,
-                    depth + 1,
-                    bnb_tries,
-                    rng,
-                    match_parameters,
-                )
+    let mut depth = 0;
+    for _ in 0..BNB_TOTAL_TRIES {
+        let mut backtrack = false;
+        if current_value + current_available < target
+            || current_value > upper_bound
+            || (is_feerate_high && current_waste > best_waste)
+        {
+            // Either the branch can't reach the target, it overshoots the match range, or with
+            // a high feerate adding more inputs can only increase the waste further
+            backtrack = true;
+        } else if current_value >= target {
+            // Changeless solution found, the excess counts towards waste
+            let waste = current_waste + (current_value - target) as i64;
+            if waste <= best_waste {
+                best_waste = waste;
+                best_selection = Some(current_selection.clone());
             }
+            backtrack = true;
         }
-    } else {
-        match bnb(
-            inputs_in_desc_value,
-            selected_inputs,
-            acc_eff_value,
-            depth + 1,
-            bnb_tries,
-            rng,
-            match_parameters,
-        ) {
-            Some(without_this) => Some(without_this),
-            None => {
-                let new_effective_value = acc_eff_value
-                    + effective_value(
-                        inputs_in_desc_value[depth].1,
-                        match_parameters.target_feerate,
-                    )
-                    .unwrap_or_default();
-                selected_inputs.push(inputs_in_desc_value[depth].0);
-                let with_this = bnb(
-                    inputs_in_desc_value,
-                    selected_inputs,
-                    new_effective_value,
-                    depth + 1,
-                    bnb_tries,
-                    rng,
-                    match_parameters,
-                );
-                match with_this {
-                    Some(_) => with_this,
-                    None => {
-                        selected_inputs.pop(); // poping out the selected utxo if it does not fit
-                        None
-                    }
-                }
+
+        if backtrack {
+            let last_included = match current_selection.last() {
+                Some(&last_included) => last_included,
+                // The whole tree has been explored
+                None => break,
+            };
+            // Omitted inputs after the last included one become available again
+            while depth > last_included + 1 {
+                depth -= 1;
+                current_available += candidates[depth].effective_value;
+            }
+            // Move to the omission branch of the last included input
+            depth = last_included;
+            let candidate = &candidates[depth];
+            current_value -= candidate.effective_value;
+            current_waste -= candidate.timing_cost;
+            current_selection.pop();
+        } else {
+            let candidate = &candidates[depth];
+            current_available -= candidate.effective_value;
+            // Skip the inclusion branch if the previous candidate is equivalent and was omitted,
+            // since that branch has already been explored
+            let previous_omitted_equivalent = depth > 0
+                && current_selection.last() != Some(&(depth - 1))
+                && candidates[depth - 1].effective_value == candidate.effective_value
+                && candidates[depth - 1].timing_cost == candidate.timing_cost;
+            if !previous_omitted_equivalent {
+                current_selection.push(depth);
+                current_value += candidate.effective_value;
+                current_waste += candidate.timing_cost;
             }
         }
+        depth += 1;
+    }
+
+    best_selection.map(|selection| {
+        selection
+            .into_iter()
+            .map(|position| candidates[position].index)
+            .collect()
+    })
+}
+
+/// The Branch and Bound algorithm as a [`CoinSelectionAlgorithm`], see [`select_coin_bnb`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BranchAndBound;
+
+impl CoinSelectionAlgorithm for BranchAndBound {
+    fn name(&self) -> &str {
+        "bnb"
+    }
+
+    fn select(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+    ) -> Result<SelectionOutput, SelectionError> {
+        select_coin_bnb(inputs, options)
     }
 }
 
@@ -179,6 +204,7 @@ mod test {
         algorithms::bnb::select_coin_bnb,
         types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError},
     };
+    use alloc::{vec, vec::Vec};
 
     fn setup_basic_output_groups() -> Vec<OutputGroup> {
         vec![
@@ -187,18 +213,21 @@ mod test {
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 2000,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 3000,
                 weight: 300,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ]
     }
@@ -216,6 +245,8 @@ mod test {
             avg_output_weight: 20,
             min_change_value: 500,
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         }
     }
 
@@ -227,48 +258,56 @@ mod test {
                 weight: 500,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 400,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 40000,
                 weight: 300,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 25000,
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 35000,
                 weight: 150,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 600,
                 weight: 250,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 30000,
                 weight: 120,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 5000,
                 weight: 50,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ];
 
@@ -300,9 +339,40 @@ mod test {
         );
     }
 
+    fn test_bnb_lowest_waste() {
+        // Effective values at 0.5 sats/wu are 3020, 2000, 1000, 990 and 10
+        let inputs: Vec<OutputGroup> = [3070, 2050, 1050, 1040, 60]
+            .into_iter()
+            .map(|value| OutputGroup {
+                value,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            })
+            .collect();
+        // Target for match is 3995 + fee of the base weight = 4000, match range is 30
+        let mut options = bnb_setup_options(3995);
+
+        // Without a timing cost the exact match with four inputs has no waste
+        options.long_term_feerate = Some(0.5);
+        let result = select_coin_bnb(&inputs, &options).unwrap();
+        assert_eq!(result.selected_inputs, vec![1, 2, 3, 4]);
+        // The search is deterministic
+        let repeated = select_coin_bnb(&inputs, &options).unwrap();
+        assert_eq!(result.selected_inputs, repeated.selected_inputs);
+
+        // When the feerate is high each additional input costs 40 sats of waste, so the
+        // two input solution with an excess of 10 sats is preferred
+        options.long_term_feerate = Some(0.1);
+        let result = select_coin_bnb(&inputs, &options).unwrap();
+        assert_eq!(result.selected_inputs, vec![0, 3]);
+    }
+
     #[test]
     fn test_bnb() {
         test_bnb_solution();
         test_bnb_no_solution();
+        test_bnb_lowest_waste();
     }
 }
@@ -0,0 +1,334 @@
+use crate::{
+    algorithms::CoinSelectionAlgorithm,
+    types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput, WasteMetric},
+    utils::{
+        calculate_fee, calculate_waste, create_selection_output, effective_value,
+        select_with_input_constraints,
+    },
+};
+use alloc::{vec, vec::Vec};
+
+/// Upper bound on the number of nodes visited by the depth-first search.
+const COINGRINDER_TOTAL_TRIES: u32 = 100_000;
+
+/// An input candidate prepared for the search, with its index in the caller's slice.
+#[derive(Debug)]
+struct GrinderCandidate {
+    index: usize,
+    effective_value: u64,
+    weight: u64,
+}
+
+/// Search state of [`grind`].
+#[derive(Debug)]
+struct GrinderState {
+    target: u64,
+    tries: u32,
+    current_selection: Vec<usize>,
+    current_value: u64,
+    current_weight: u64,
+    best_selection: Option<Vec<usize>>,
+    best_value: u64,
+    best_weight: u64,
+}
+
+/// Performs coin selection using the CoinGrinder algorithm.
+///
+/// CoinGrinder searches for the input set with the lowest total weight whose effective value funds the target,
+/// the fees and a change output of at least `min_change_value`. It is meant for high feerate environments
+/// where every weight unit saved reduces the fee, as done in Bitcoin Core.
+///
+/// Returns `InsufficientFunds` if the inputs can't fund the target, and `NoSolutionFound` if no solution is found within the try budget.
+pub fn select_coin_coingrinder(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    select_with_input_constraints(inputs, options, "coingrinder", coin_grinder)
+}
+
+fn coin_grinder(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    // The change output is always created, so its weight is paid for along with the base weight
+    let target = options.target_value
+        + options.min_change_value
+        + calculate_fee(
+            options.base_weight + options.change_weight,
+            options.target_feerate,
+        )?
+        .max(options.min_absolute_fee);
+
+    let mut candidates = Vec::with_capacity(inputs.len());
+    for (index, input) in inputs.iter().enumerate() {
+        let effective_value = effective_value(input, options.target_feerate)?;
+        if effective_value == 0 {
+            continue;
+        }
+        candidates.push(GrinderCandidate {
+            index,
+            effective_value,
+            weight: input.weight,
+        });
+    }
+    // Largest effective value first, lightest first among equal values
+    candidates.sort_by(|a, b| {
+        b.effective_value
+            .cmp(&a.effective_value)
+            .then(a.weight.cmp(&b.weight))
+    });
+
+    // lookahead[i] is the effective value available from position i onwards,
+    // min_tail_weight[i] is the lightest candidate from position i onwards
+    let mut lookahead = vec![0; candidates.len() + 1];
+    let mut min_tail_weight = vec![u64::MAX; candidates.len() + 1];
+    for (position, candidate) in candidates.iter().enumerate().rev() {
+        lookahead[position] = lookahead[position + 1] + candidate.effective_value;
+        min_tail_weight[position] = min_tail_weight[position + 1].min(candidate.weight);
+    }
+
+    if lookahead[0] < target {
+        return Err(SelectionError::InsufficientFunds);
+    }
+
+    let mut state = GrinderState {
+        target,
+        tries: COINGRINDER_TOTAL_TRIES,
+        current_selection: Vec::new(),
+        current_value: 0,
+        current_weight: 0,
+        best_selection: None,
+        best_value: u64::MAX,
+        best_weight: u64::MAX,
+    };
+    grind(&candidates, &lookahead, &min_tail_weight, &mut state);
+
+    match state.best_selection {
+        Some(selected_inputs) => {
+            let accumulated_value: u64 = selected_inputs.iter().map(|&i| inputs[i].value).sum();
+            let accumulated_weight: u64 = selected_inputs.iter().map(|&i| inputs[i].weight).sum();
+            let estimated_fees = calculate_fee(accumulated_weight, options.target_feerate)?;
+            let waste = calculate_waste(
+                options,
+                accumulated_value,
+                accumulated_weight,
+                estimated_fees,
+            );
+            create_selection_output(
+                inputs,
+                options,
+                selected_inputs,
+                WasteMetric(waste),
+                "coingrinder",
+            )
+        }
+        None => Err(SelectionError::NoSolutionFound),
+    }
+}
+
+/// A step of the depth-first search of [`grind`].
+#[derive(Debug, Clone, Copy)]
+enum GrinderStep {
+    /// Explores the branches of the candidate at this position, with the candidates before it decided.
+    Visit(usize),
+    /// Removes the candidate at this position from the current selection, once its inclusion branch is explored.
+    Omit(usize),
+}
+
+/// Depth-first search over the candidates, inclusion branch first.
+///
+/// A branch is cut when it can no longer reach the target, or when it can't beat the weight of the best solution found so far.
+/// The branches left to explore are kept on an explicit stack, so the search depth isn't bounded by the thread stack.
+fn grind(
+    candidates: &[GrinderCandidate],
+    lookahead: &[u64],
+    min_tail_weight: &[u64],
+    state: &mut GrinderState,
+) {
+    let mut steps = vec![GrinderStep::Visit(0)];
+    while let Some(step) = steps.pop() {
+        let depth = match step {
+            GrinderStep::Visit(depth) => depth,
+            GrinderStep::Omit(depth) => {
+                let candidate = &candidates[depth];
+                state.current_selection.pop();
+                state.current_value -= candidate.effective_value;
+                state.current_weight -= candidate.weight;
+                continue;
+            }
+        };
+        if state.tries == 0 {
+            return;
+        }
+        state.tries -= 1;
+
+        if state.current_value >= state.target {
+            // Adding more inputs can only increase the weight, so this branch ends here.
+            // Among solutions of equal weight, the one with less excess is preferred.
+            if state.current_weight < state.best_weight
+                || (state.current_weight == state.best_weight
+                    && state.current_value < state.best_value)
+            {
+                state.best_weight = state.current_weight;
+                state.best_value = state.current_value;
+                state.best_selection = Some(
+                    state
+                        .current_selection
+                        .iter()
+                        .map(|&position| candidates[position].index)
+                        .collect(),
+                );
+            }
+            continue;
+        }
+
+        if depth == candidates.len()
+            || state.current_value + lookahead[depth] < state.target
+            || state.current_weight + min_tail_weight[depth] > state.best_weight
+        {
+            continue;
+        }
+
+        // Omission branch, explored last. Candidates equivalent to the omitted one are skipped as well,
+        // since including them instead leads to selections already explored in the inclusion branch.
+        let candidate = &candidates[depth];
+        let mut next = depth + 1;
+        while next < candidates.len()
+            && candidates[next].effective_value == candidate.effective_value
+            && candidates[next].weight == candidate.weight
+        {
+            next += 1;
+        }
+        steps.push(GrinderStep::Visit(next));
+
+        // Inclusion branch, explored first
+        state.current_selection.push(depth);
+        state.current_value += candidate.effective_value;
+        state.current_weight += candidate.weight;
+        steps.push(GrinderStep::Omit(depth));
+        steps.push(GrinderStep::Visit(depth + 1));
+    }
+}
+
+/// The CoinGrinder algorithm as a [`CoinSelectionAlgorithm`], see [`select_coin_coingrinder`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoinGrinder;
+
+impl CoinSelectionAlgorithm for CoinGrinder {
+    fn name(&self) -> &str {
+        "coingrinder"
+    }
+
+    fn select(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+    ) -> Result<SelectionOutput, SelectionError> {
+        select_coin_coingrinder(inputs, options)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        algorithms::coingrinder::select_coin_coingrinder,
+        types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError},
+    };
+    use alloc::{vec, vec::Vec};
+
+    fn setup_coingrinder_output_groups() -> Vec<OutputGroup> {
+        vec![
+            OutputGroup {
+                value: 6000,
+                weight: 400,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+            OutputGroup {
+                value: 3000,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+            OutputGroup {
+                value: 3000,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+            OutputGroup {
+                value: 5000,
+                weight: 250,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn setup_options(target_value: u64) -> CoinSelectionOpt {
+        CoinSelectionOpt {
+            target_value,
+            target_feerate: 0.5, // Simplified feerate
+            long_term_feerate: Some(0.4),
+            min_absolute_fee: 0,
+            base_weight: 10,
+            change_weight: 50,
+            change_cost: 10,
+            avg_input_weight: 20,
+            avg_output_weight: 10,
+            min_change_value: 500,
+            excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
+        }
+    }
+
+    #[test]
+    fn test_coingrinder_minimizes_weight() {
+        let inputs = setup_coingrinder_output_groups();
+        // Effective values are 5800, 2950, 2950 and 4875, the target with change and fees is 4530.
+        // Both the first and the last input fund it alone, but the two light inputs weigh less.
+        let options = setup_options(4000);
+        let result = select_coin_coingrinder(&inputs, &options).unwrap();
+        let mut selected_inputs = result.selected_inputs.clone();
+        selected_inputs.sort();
+        assert_eq!(selected_inputs, vec![1, 2]);
+
+        // A target of 5930 needs two inputs, the lightest pair that funds it leaves out the heaviest input
+        let options = setup_options(5400);
+        let result = select_coin_coingrinder(&inputs, &options).unwrap();
+        let mut selected_inputs = result.selected_inputs.clone();
+        selected_inputs.sort();
+        assert_eq!(selected_inputs, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_coingrinder_many_inputs() {
+        // Funding the target takes nearly all of the inputs, as deep a search as there are inputs
+        let inputs: Vec<OutputGroup> = (0..20_000)
+            .map(|_| OutputGroup {
+                value: 1000,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            })
+            .collect();
+        let options = setup_options(18_000_000);
+        let result = select_coin_coingrinder(&inputs, &options).unwrap();
+        assert_eq!(result.selected_inputs.len(), 18_948);
+    }
+
+    #[test]
+    fn test_coingrinder_insufficient_funds() {
+        let inputs = setup_coingrinder_output_groups();
+        let options = setup_options(20000);
+        let result = select_coin_coingrinder(&inputs, &options);
+        assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
+    }
+}
@@ -1,7 +1,11 @@
 use crate::{
+    algorithms::CoinSelectionAlgorithm,
     types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput, WasteMetric},
-    utils::{calculate_fee, calculate_waste},
+    utils::{
+        calculate_fee, calculate_waste, create_selection_output, select_with_input_constraints,
+    },
 };
+use alloc::vec::Vec;
 
 /// Performs coin selection using the First-In-First-Out (FIFO) algorithm.
 ///
@@ -9,6 +13,13 @@ use crate::{
 pub fn select_coin_fifo(
     inputs: &[OutputGroup],
     options: &CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    select_with_input_constraints(inputs, options, "fifo", fifo)
+}
+
+fn fifo(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
 ) -> Result<SelectionOutput, SelectionError> {
     let mut accumulated_value: u64 = 0;
     let mut accumulated_weight: u64 = 0;
@@ -22,7 +33,7 @@ pub fn select_coin_fifo(
         .filter(|(_, og)| og.creation_sequence.is_some())
         .collect();
 
-    sorted_inputs.sort_by(|a, b| a.1.creation_sequence.cmp(&b.1.creation_sequence));
+    sorted_inputs.sort_by_key(|a| a.1.creation_sequence);
 
     let inputs_without_sequence: Vec<_> = inputs
         .iter()
@@ -53,16 +64,31 @@ pub fn select_coin_fifo(
     {
         Err(SelectionError::InsufficientFunds)
     } else {
-        let waste: u64 = calculate_waste(
+        let waste: i64 = calculate_waste(
             options,
             accumulated_value,
             accumulated_weight,
             estimated_fees,
         );
-        Ok(SelectionOutput {
-            selected_inputs,
-            waste: WasteMetric(waste),
-        })
+        create_selection_output(inputs, options, selected_inputs, WasteMetric(waste), "fifo")
+    }
+}
+
+/// The First-In-First-Out algorithm as a [`CoinSelectionAlgorithm`], see [`select_coin_fifo`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Fifo;
+
+impl CoinSelectionAlgorithm for Fifo {
+    fn name(&self) -> &str {
+        "fifo"
+    }
+
+    fn select(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+    ) -> Result<SelectionOutput, SelectionError> {
+        select_coin_fifo(inputs, options)
     }
 }
 
@@ -73,6 +99,7 @@ mod test {
         algorithms::fifo::select_coin_fifo,
         types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError},
     };
+    use alloc::{vec, vec::Vec};
 
     fn setup_basic_output_groups() -> Vec<OutputGroup> {
         vec![
@@ -81,18 +108,21 @@ mod test {
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 2000,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 3000,
                 weight: 300,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ]
     }
@@ -103,24 +133,28 @@ mod test {
                 weight: 100,
                 input_count: 1,
                 creation_sequence: Some(1),
+                ..Default::default()
             },
             OutputGroup {
                 value: 2000,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: Some(5000),
+                ..Default::default()
             },
             OutputGroup {
                 value: 3000,
                 weight: 300,
                 input_count: 1,
                 creation_sequence: Some(1001),
+                ..Default::default()
             },
             OutputGroup {
                 value: 1500,
                 weight: 150,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ]
     }
@@ -138,6 +172,8 @@ mod test {
             avg_output_weight: 10,
             min_change_value: 500,
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         }
     }
 
@@ -1,16 +1,48 @@
 use crate::{
+    algorithms::CoinSelectionAlgorithm,
     types::{
         CoinSelectionOpt, EffectiveValue, OutputGroup, SelectionError, SelectionOutput,
         WasteMetric, Weight,
     },
-    utils::{calculate_accumulated_weight, calculate_fee, calculate_waste, effective_value},
+    utils::{
+        calculate_accumulated_weight, calculate_fee, calculate_waste, create_selection_output,
+        effective_value, select_with_input_constraints,
+    },
 };
-use rand::{thread_rng, Rng};
-use std::{cmp::Reverse, collections::HashSet};
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::cmp::Reverse;
+use rand::{Rng, RngCore};
 
+/// Performs coin selection using the Knapsack algorithm.
+///
+/// Only available with the `std` feature, which supplies the coin tosses.
+/// Without it, use [`select_coin_knapsack_with_rng`].
+#[cfg(feature = "std")]
 pub fn select_coin_knapsack(
     inputs: &[OutputGroup],
     options: &CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    select_coin_knapsack_with_rng(inputs, options, &mut crate::algorithms::default_rng())
+}
+
+/// Performs coin selection using the Knapsack algorithm, with the coin tosses drawn from `rng`.
+///
+/// The same seeded `rng`, inputs and options always produce the same selection.
+pub fn select_coin_knapsack_with_rng<R: RngCore + ?Sized>(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    rng: &mut R,
+) -> Result<SelectionOutput, SelectionError> {
+    select_with_input_constraints(inputs, options, "knapsack", |inputs, options| {
+        knapsack(inputs, options, rng)
+    })
+}
+
+/// Runs [`knap_sack`] over the inputs smaller than the adjusted target, sorted by descending effective value.
+fn knapsack<R: RngCore + ?Sized>(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    rng: &mut R,
 ) -> Result<SelectionOutput, SelectionError> {
     let adjusted_target = options.target_value
         + options.min_change_value
@@ -32,19 +64,20 @@ pub fn select_coin_knapsack(
         .into_iter()
         .filter_map(|(index, value, weight)| value.ok().map(|v| (index, v, weight)))
         .collect();
-    knap_sack(adjusted_target, &smaller_coins, options)
+    knap_sack(inputs, adjusted_target, &smaller_coins, options, rng)
 }
 
-fn knap_sack(
+fn knap_sack<R: RngCore + ?Sized>(
+    inputs: &[OutputGroup],
     adjusted_target: u64,
     smaller_coins: &[(usize, EffectiveValue, Weight)],
     options: &CoinSelectionOpt,
+    rng: &mut R,
 ) -> Result<SelectionOutput, SelectionError> {
-    let mut selected_inputs: HashSet<usize> = HashSet::new();
+    let mut selected_inputs: BTreeSet<usize> = BTreeSet::new();
     let mut accumulated_value: u64 = 0;
-    let mut best_set: HashSet<usize> = HashSet::new();
+    let mut best_set: BTreeSet<usize> = BTreeSet::new();
     let mut best_set_value: u64 = u64::MAX;
-    let mut rng = thread_rng();
     for _ in 1..=1000 {
         for pass in 1..=2 {
             for &(index, value, _) in smaller_coins {
@@ -57,17 +90,21 @@ fn knap_sack(
                             calculate_accumulated_weight(smaller_coins, &selected_inputs);
                         let estimated_fees =
                             calculate_fee(accumulated_weight, options.target_feerate);
-                        let index_vector: Vec<usize> = selected_inputs.into_iter().collect();
-                        let waste: u64 = calculate_waste(
+                        let mut index_vector: Vec<usize> = selected_inputs.into_iter().collect();
+                        index_vector.sort_unstable();
+                        let waste: i64 = calculate_waste(
                             options,
                             accumulated_value,
                             accumulated_weight,
                             estimated_fees?,
                         );
-                        return Ok(SelectionOutput {
-                            selected_inputs: index_vector,
-                            waste: WasteMetric(waste),
-                        });
+                        return create_selection_output(
+                            inputs,
+                            options,
+                            index_vector,
+                            WasteMetric(waste),
+                            "knapsack",
+                        );
                     } else if accumulated_value >= adjusted_target {
                         if accumulated_value < best_set_value {
                             best_set_value = accumulated_value;
@@ -87,27 +124,73 @@ fn knap_sack(
     } else {
         let best_set_weight = calculate_accumulated_weight(smaller_coins, &best_set);
         let estimated_fees = calculate_fee(best_set_weight, options.target_feerate);
-        let index_vector: Vec<usize> = best_set.into_iter().collect();
-        let waste: u64 = calculate_waste(options, best_set_value, best_set_weight, estimated_fees?);
-        Ok(SelectionOutput {
-            selected_inputs: index_vector,
-            waste: WasteMetric(waste),
-        })
+        let mut index_vector: Vec<usize> = best_set.into_iter().collect();
+        index_vector.sort_unstable();
+        let waste: i64 = calculate_waste(options, best_set_value, best_set_weight, estimated_fees?);
+        create_selection_output(
+            inputs,
+            options,
+            index_vector,
+            WasteMetric(waste),
+            "knapsack",
+        )
+    }
+}
+
+/// The Knapsack algorithm as a [`CoinSelectionAlgorithm`], see [`select_coin_knapsack`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Knapsack;
+
+impl CoinSelectionAlgorithm for Knapsack {
+    fn name(&self) -> &str {
+        "knapsack"
+    }
+
+    fn select(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+    ) -> Result<SelectionOutput, SelectionError> {
+        #[cfg(feature = "std")]
+        {
+            select_coin_knapsack(inputs, options)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = (inputs, options);
+            Err(SelectionError::NoSolutionFound)
+        }
+    }
+
+    fn select_with_rng(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+        rng: &mut dyn RngCore,
+    ) -> Result<SelectionOutput, SelectionError> {
+        select_coin_knapsack_with_rng(inputs, options, rng)
     }
 }
 
 #[cfg(test)]
 mod test {
 
+    #[cfg(feature = "std")]
+    use crate::{algorithms::knapsack::select_coin_knapsack, types::SelectionError};
     use crate::{
-        algorithms::knapsack::select_coin_knapsack,
-        types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError},
+        algorithms::knapsack::select_coin_knapsack_with_rng,
+        types::{CoinSelectionOpt, ExcessStrategy, OutputGroup},
         utils::calculate_fee,
     };
+    use alloc::{vec, vec::Vec};
+    use rand::{rngs::StdRng, SeedableRng};
 
+    #[cfg(feature = "std")]
     const CENT: f64 = 1000000.0;
     const COIN: f64 = 100000000.0;
+    #[cfg(feature = "std")]
     const RUN_TESTS: u32 = 100;
+    #[cfg(feature = "std")]
     const RUN_TESTS_SLIM: u32 = 10;
 
     fn knapsack_setup_options(adjusted_target: u64, target_feerate: f32) -> CoinSelectionOpt {
@@ -128,6 +211,8 @@ mod test {
             avg_output_weight: 10,
             min_change_value,
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         }
     }
 
@@ -137,7 +222,7 @@ mod test {
         target_feerate: f32,
     ) -> Vec<OutputGroup> {
         let mut inputs: Vec<OutputGroup> = Vec::new();
-        for (i, j) in value.into_iter().zip(weights.into_iter()) {
+        for (i, j) in value.into_iter().zip(weights) {
             // input value = effective value + fees
             // Example If we want our input to be equal to 1 CENT while being considered by knapsack(effective value), we have to increase the input by the fees to beginwith
             let k = i.saturating_add(calculate_fee(j, target_feerate).unwrap_or_default());
@@ -146,18 +231,20 @@ mod test {
                 weight: j,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             })
         }
         inputs
     }
 
+    #[cfg(feature = "std")]
     fn knapsack_add_to_output_group(
         inputs: &mut Vec<OutputGroup>,
         value: Vec<u64>,
         weights: Vec<u64>,
         target_feerate: f32,
     ) {
-        for (i, j) in value.into_iter().zip(weights.into_iter()) {
+        for (i, j) in value.into_iter().zip(weights) {
             // input value = effective value + fees
             // Example If we want our input to be equal to 1 CENT while being considered by knapsack(effective value), we have to increase the input by the fees to beginwith
             let k = i.saturating_add(calculate_fee(j, target_feerate).unwrap_or_default());
@@ -166,10 +253,12 @@ mod test {
                 weight: j,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             })
         }
     }
 
+    #[cfg(feature = "std")]
     fn knapsack_test_vectors() {
         let mut inputs_verify: Vec<usize> = Vec::new();
         for _ in 0..RUN_TESTS {
@@ -457,6 +546,8 @@ mod test {
                 avg_output_weight: 10,
                 min_change_value: (0.05 * CENT).round() as u64, // Setting minimum change value = 0.05 CENT. This will make the algorithm to avoid creating small change.
                 excess_strategy: ExcessStrategy::ToChange,
+                must_spend: vec![],
+                excluded: vec![],
             };
             if let Ok(result) = select_coin_knapsack(&inputs, &options) {
                 // Chekcing if knapsack selects exactly 2 inputs
@@ -553,8 +644,23 @@ mod test {
         );
     }
 
+    fn knapsack_seeded_selection() {
+        // 100 identical inputs of 1 COIN, any 50 of them fund the target
+        let inputs = knapsack_setup_output_groups(vec![COIN as u64; 100], vec![23; 100], 0.34);
+        let options = knapsack_setup_options((50.0 * COIN).round() as u64, 0.34);
+        let first = select_coin_knapsack_with_rng(&inputs, &options, &mut StdRng::seed_from_u64(7));
+        let second =
+            select_coin_knapsack_with_rng(&inputs, &options, &mut StdRng::seed_from_u64(7));
+        assert_eq!(
+            first.unwrap().selected_inputs,
+            second.unwrap().selected_inputs
+        );
+    }
+
     #[test]
     fn test_knapsack() {
+        #[cfg(feature = "std")]
         knapsack_test_vectors();
+        knapsack_seeded_selection();
     }
 }
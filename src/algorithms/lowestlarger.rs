@@ -1,7 +1,12 @@
 use crate::{
+    algorithms::CoinSelectionAlgorithm,
     types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput, WasteMetric},
-    utils::{calculate_fee, calculate_waste, effective_value},
+    utils::{
+        calculate_fee, calculate_waste, create_selection_output, effective_value,
+        select_with_input_constraints,
+    },
 };
+use alloc::vec::Vec;
 
 /// Performs coin selection using the Lowest Larger algorithm.
 ///
@@ -9,6 +14,13 @@ use crate::{
 pub fn select_coin_lowestlarger(
     inputs: &[OutputGroup],
     options: &CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    select_with_input_constraints(inputs, options, "lowestlarger", lowest_larger)
+}
+
+fn lowest_larger(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
 ) -> Result<SelectionOutput, SelectionError> {
     let mut accumulated_value: u64 = 0;
     let mut accumulated_weight: u64 = 0;
@@ -51,16 +63,37 @@ pub fn select_coin_lowestlarger(
     if accumulated_value < (target + estimated_fees.max(options.min_absolute_fee)) {
         Err(SelectionError::InsufficientFunds)
     } else {
-        let waste: u64 = calculate_waste(
+        let waste: i64 = calculate_waste(
             options,
             accumulated_value,
             accumulated_weight,
             estimated_fees,
         );
-        Ok(SelectionOutput {
+        create_selection_output(
+            inputs,
+            options,
             selected_inputs,
-            waste: WasteMetric(waste),
-        })
+            WasteMetric(waste),
+            "lowestlarger",
+        )
+    }
+}
+
+/// The Lowest Larger algorithm as a [`CoinSelectionAlgorithm`], see [`select_coin_lowestlarger`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LowestLarger;
+
+impl CoinSelectionAlgorithm for LowestLarger {
+    fn name(&self) -> &str {
+        "lowestlarger"
+    }
+
+    fn select(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+    ) -> Result<SelectionOutput, SelectionError> {
+        select_coin_lowestlarger(inputs, options)
     }
 }
 
@@ -71,6 +104,7 @@ mod test {
         algorithms::lowestlarger::select_coin_lowestlarger,
         types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError},
     };
+    use alloc::{vec, vec::Vec};
 
     fn setup_lowestlarger_output_groups() -> Vec<OutputGroup> {
         vec![
@@ -79,72 +113,84 @@ mod test {
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 1500,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 3400,
                 weight: 300,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 2200,
                 weight: 150,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 1190,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 3300,
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 1000,
                 weight: 190,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 2000,
                 weight: 210,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 3000,
                 weight: 300,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 2250,
                 weight: 250,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 190,
                 weight: 220,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 1750,
                 weight: 170,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ]
     }
@@ -162,6 +208,8 @@ mod test {
             avg_output_weight: 10,
             min_change_value: 500,
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         }
     }
 
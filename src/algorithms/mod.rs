@@ -1,5 +1,50 @@
+use crate::types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput};
+use rand::RngCore;
+
 pub mod bnb;
+pub mod coingrinder;
 pub mod fifo;
 pub mod knapsack;
 pub mod lowestlarger;
 pub mod srd;
+
+/// A coin selection algorithm that can be registered in a [`CoinSelector`](crate::selectcoin::CoinSelector).
+///
+/// All the algorithms of this library implement it, and users can implement it to plug in their own.
+pub trait CoinSelectionAlgorithm: Send + Sync {
+    /// Name identifying the algorithm in a [`CoinSelector`](crate::selectcoin::CoinSelector).
+    fn name(&self) -> &str;
+
+    /// Selects a subset of `inputs` satisfying `options`.
+    ///
+    /// The indices of the returned [`SelectionOutput`] refer to the `inputs` slice.
+    /// Without the `std` feature, randomized algorithms have no source of entropy and fail with `NoSolutionFound`:
+    /// they only select through [`select_with_rng`](CoinSelectionAlgorithm::select_with_rng).
+    fn select(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+    ) -> Result<SelectionOutput, SelectionError>;
+
+    /// Same as [`select`](CoinSelectionAlgorithm::select), drawing any randomness from `rng`.
+    ///
+    /// Randomized algorithms must override it so that a seeded `rng` makes their selection reproducible.
+    /// The default implementation ignores `rng`, which suits deterministic algorithms.
+    fn select_with_rng(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+        _rng: &mut dyn RngCore,
+    ) -> Result<SelectionOutput, SelectionError> {
+        self.select(inputs, options)
+    }
+}
+
+/// Returns the rng of the randomized algorithms when none is given.
+///
+/// Only available with the `std` feature: without it there is no source of entropy, and the randomized algorithms
+/// must be given an rng or a seed.
+#[cfg(feature = "std")]
+pub(crate) fn default_rng() -> rand::rngs::ThreadRng {
+    rand::thread_rng()
+}
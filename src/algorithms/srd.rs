@@ -1,23 +1,49 @@
 use crate::{
+    algorithms::CoinSelectionAlgorithm,
     types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput, WasteMetric},
-    utils::{calculate_fee, calculate_waste},
+    utils::{
+        calculate_fee, calculate_waste, create_selection_output, select_with_input_constraints,
+    },
 };
-use rand::{seq::SliceRandom, thread_rng};
+use alloc::vec::Vec;
+use rand::{seq::SliceRandom, RngCore};
 
 /// Performs coin selection using a single random draw.
 ///
 /// Returns `NoSolutionFound` if no solution is found.
+/// Only available with the `std` feature, which supplies the draw. Without it, use [`select_coin_srd_with_rng`].
+#[cfg(feature = "std")]
 pub fn select_coin_srd(
     inputs: &[OutputGroup],
     options: &CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    select_coin_srd_with_rng(inputs, options, &mut crate::algorithms::default_rng())
+}
+
+/// Performs coin selection using a single random draw, with the randomness drawn from `rng`.
+///
+/// The same seeded `rng`, inputs and options always produce the same selection.
+pub fn select_coin_srd_with_rng<R: RngCore + ?Sized>(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    rng: &mut R,
+) -> Result<SelectionOutput, SelectionError> {
+    select_with_input_constraints(inputs, options, "srd", |inputs, options| {
+        srd(inputs, options, rng)
+    })
+}
+
+fn srd<R: RngCore + ?Sized>(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    rng: &mut R,
 ) -> Result<SelectionOutput, SelectionError> {
     // In out put we need to specify the indexes of the inputs in the given order
     // So keep track of the indexes when randomiz ing the vec
     let mut randomized_inputs: Vec<_> = inputs.iter().enumerate().collect();
 
     // Randomize the inputs order to simulate the random draw
-    let mut rng = thread_rng();
-    randomized_inputs.shuffle(&mut rng);
+    randomized_inputs.shuffle(rng);
 
     let mut accumulated_value = 0;
     let mut selected_inputs = Vec::new();
@@ -56,19 +82,55 @@ pub fn select_coin_srd(
         estimated_fee,
     );
 
-    Ok(SelectionOutput {
-        selected_inputs,
-        waste: WasteMetric(waste),
-    })
+    create_selection_output(inputs, options, selected_inputs, WasteMetric(waste), "srd")
+}
+
+/// The Single Random Draw algorithm as a [`CoinSelectionAlgorithm`], see [`select_coin_srd`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SingleRandomDraw;
+
+impl CoinSelectionAlgorithm for SingleRandomDraw {
+    fn name(&self) -> &str {
+        "srd"
+    }
+
+    fn select(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+    ) -> Result<SelectionOutput, SelectionError> {
+        #[cfg(feature = "std")]
+        {
+            select_coin_srd(inputs, options)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = (inputs, options);
+            Err(SelectionError::NoSolutionFound)
+        }
+    }
+
+    fn select_with_rng(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+        rng: &mut dyn RngCore,
+    ) -> Result<SelectionOutput, SelectionError> {
+        select_coin_srd_with_rng(inputs, options, rng)
+    }
 }
 
 #[cfg(test)]
 mod test {
 
+    #[cfg(feature = "std")]
+    use crate::algorithms::srd::select_coin_srd;
     use crate::{
-        algorithms::srd::select_coin_srd,
+        algorithms::srd::select_coin_srd_with_rng,
         types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError},
     };
+    use alloc::{vec, vec::Vec};
+    use rand::{rngs::StdRng, SeedableRng};
 
     fn setup_basic_output_groups() -> Vec<OutputGroup> {
         vec![
@@ -77,18 +139,21 @@ mod test {
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 2000,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 3000,
                 weight: 300,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ]
     }
@@ -100,24 +165,28 @@ mod test {
                 weight: 100,
                 input_count: 1,
                 creation_sequence: Some(1),
+                ..Default::default()
             },
             OutputGroup {
                 value: 2000,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: Some(5000),
+                ..Default::default()
             },
             OutputGroup {
                 value: 3000,
                 weight: 300,
                 input_count: 1,
                 creation_sequence: Some(1001),
+                ..Default::default()
             },
             OutputGroup {
                 value: 1500,
                 weight: 150,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ]
     }
@@ -135,9 +204,12 @@ mod test {
             avg_output_weight: 10,
             min_change_value: 500,
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         }
     }
 
+    #[cfg(feature = "std")]
     fn test_successful_selection() {
         let mut inputs = setup_basic_output_groups();
         let mut options = setup_options(2500);
@@ -154,6 +226,7 @@ mod test {
         assert!(!selection_output.selected_inputs.is_empty());
     }
 
+    #[cfg(feature = "std")]
     fn test_insufficient_funds() {
         let inputs = setup_basic_output_groups();
         let options = setup_options(7000); // Set a target value higher than the sum of all inputs
@@ -161,9 +234,29 @@ mod test {
         assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
     }
 
+    fn test_seeded_selection() {
+        let inputs = setup_output_groups_withsequence();
+        let options = setup_options(500);
+        let first = select_coin_srd_with_rng(&inputs, &options, &mut StdRng::seed_from_u64(42));
+        let second = select_coin_srd_with_rng(&inputs, &options, &mut StdRng::seed_from_u64(42));
+        assert_eq!(
+            first.unwrap().selected_inputs,
+            second.unwrap().selected_inputs
+        );
+
+        let inputs = setup_basic_output_groups();
+        let options = setup_options(7000);
+        let result = select_coin_srd_with_rng(&inputs, &options, &mut StdRng::seed_from_u64(42));
+        assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
+    }
+
     #[test]
     fn test_srd() {
-        test_successful_selection();
-        test_insufficient_funds();
+        #[cfg(feature = "std")]
+        {
+            test_successful_selection();
+            test_insufficient_funds();
+        }
+        test_seeded_selection();
     }
 }
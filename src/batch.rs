@@ -0,0 +1,273 @@
+use crate::{
+    types::{CoinSelectionOpt, OutputGroup, Recipient, SelectionError},
+    utils::{calculate_fee, effective_value},
+};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+impl CoinSelectionOpt {
+    /// Returns the options for a transaction paying all the `recipients`.
+    ///
+    /// `base_weight` is the weight of the transaction without any output, such as the header and the input and output
+    /// counts. The options' `base_weight` becomes it plus the weights of the recipient outputs, `target_value` the sum of
+    /// their values and `avg_output_weight` the average of their weights. The outputs the options previously paid are
+    /// replaced, so the result doesn't depend on them.
+    pub fn with_recipients(mut self, base_weight: u64, recipients: &[Recipient]) -> Self {
+        let total_weight: u64 = recipients.iter().map(|recipient| recipient.weight).sum();
+        self.target_value = recipients.iter().map(|recipient| recipient.value).sum();
+        self.base_weight = base_weight + total_weight;
+        if !recipients.is_empty() {
+            self.avg_output_weight = total_weight / recipients.len() as u64;
+        }
+        self
+    }
+}
+
+/// The payouts that fit in a single transaction, as decided by [`batch_payouts`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PayoutBatch {
+    /// Indices of the pending payouts included in the transaction, in queue order.
+    pub included: Vec<usize>,
+    /// Indices of the pending payouts left for a later transaction, in queue order.
+    pub deferred: Vec<usize>,
+    /// Options paying all the included payouts, to be used for the selection.
+    pub options: CoinSelectionOpt,
+}
+
+/// Decides which of the `pending` payouts fit into one transaction funded by `inputs`.
+///
+/// Payouts are considered in queue order, and one is included if the available inputs can still fund all the included
+/// payouts, the fees and a change output of at least `min_change_value`, without the transaction exceeding `max_weight`.
+/// A payout that doesn't fit is deferred and the following ones are still considered, so a large payout doesn't block
+/// the smaller ones behind it.
+///
+/// The `base_weight` of `options` excludes any output, and is given to [`CoinSelectionOpt::with_recipients`].
+/// The must-spend inputs are always counted, and the excluded inputs never are. Returns `MustSpendOutOfRange` if a
+/// must-spend index is out of range of `inputs`.
+pub fn batch_payouts(
+    pending: &[Recipient],
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    max_weight: u64,
+) -> Result<PayoutBatch, SelectionError> {
+    if options
+        .must_spend
+        .iter()
+        .any(|&index| index >= inputs.len())
+    {
+        return Err(SelectionError::MustSpendOutOfRange);
+    }
+    // Must-spend inputs come first, then the remaining ones by descending effective value,
+    // which funds a target with as few inputs as possible
+    let mut must_spend: Vec<(u64, u64)> = Vec::new();
+    let mut others: Vec<(u64, u64)> = Vec::new();
+    for (index, input) in inputs.iter().enumerate() {
+        let candidate = (
+            effective_value(input, options.target_feerate)?,
+            input.weight,
+        );
+        if options.must_spend.contains(&index) {
+            must_spend.push(candidate);
+        } else if !options.excluded.contains(&index) {
+            others.push(candidate);
+        }
+    }
+    others.sort_by_key(|&(effective_value, _)| Reverse(effective_value));
+    let must_spend_count = must_spend.len();
+    must_spend.extend(others);
+    let candidates = must_spend;
+
+    let mut included = Vec::new();
+    let mut deferred = Vec::new();
+    let mut batch_value: u64 = 0;
+    let mut batch_weight: u64 = 0;
+    for (index, payout) in pending.iter().enumerate() {
+        let fits = fits_in_transaction(
+            &candidates,
+            must_spend_count,
+            options,
+            batch_value + payout.value,
+            batch_weight + payout.weight,
+            max_weight,
+        )?;
+        if fits {
+            included.push(index);
+            batch_value += payout.value;
+            batch_weight += payout.weight;
+        } else {
+            deferred.push(index);
+        }
+    }
+
+    let recipients: Vec<Recipient> = included.iter().map(|&index| pending[index]).collect();
+    Ok(PayoutBatch {
+        included,
+        deferred,
+        options: options
+            .clone()
+            .with_recipients(options.base_weight, &recipients),
+    })
+}
+
+/// Returns whether `candidates`, taken in order, fund payouts of `payouts_value` and `payouts_weight` within `max_weight`.
+///
+/// The first `must_spend_count` candidates are the must-spend inputs, which are always spent.
+fn fits_in_transaction(
+    candidates: &[(u64, u64)],
+    must_spend_count: usize,
+    options: &CoinSelectionOpt,
+    payouts_value: u64,
+    payouts_weight: u64,
+    max_weight: u64,
+) -> Result<bool, SelectionError> {
+    let outputs_weight = options.base_weight + payouts_weight + options.change_weight;
+    let target = payouts_value
+        + options.min_change_value
+        + calculate_fee(outputs_weight, options.target_feerate)?.max(options.min_absolute_fee);
+    let mut accumulated_value: u64 = 0;
+    let mut accumulated_weight: u64 = outputs_weight;
+    let (must_spend, others) = candidates.split_at(must_spend_count);
+    for &(effective_value, weight) in must_spend {
+        accumulated_value += effective_value;
+        accumulated_weight += weight;
+    }
+    for &(effective_value, weight) in others {
+        if accumulated_value >= target {
+            break;
+        }
+        accumulated_value += effective_value;
+        accumulated_weight += weight;
+    }
+    Ok(accumulated_value >= target && accumulated_weight <= max_weight)
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        batch::batch_payouts,
+        selectcoin::select_coin,
+        types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, Recipient, SelectionError},
+    };
+    use alloc::{vec, vec::Vec};
+
+    fn setup_basic_output_groups() -> Vec<OutputGroup> {
+        vec![
+            OutputGroup {
+                value: 5000,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+            OutputGroup {
+                value: 3000,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+            OutputGroup {
+                value: 2000,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn setup_options() -> CoinSelectionOpt {
+        CoinSelectionOpt {
+            target_value: 0,
+            target_feerate: 0.4, // Simplified feerate
+            long_term_feerate: Some(0.4),
+            min_absolute_fee: 0,
+            base_weight: 40,
+            change_weight: 50,
+            change_cost: 10,
+            avg_input_weight: 20,
+            avg_output_weight: 10,
+            min_change_value: 500,
+            excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
+        }
+    }
+
+    #[test]
+    fn test_with_recipients() {
+        let recipients = [
+            Recipient {
+                value: 1000,
+                weight: 124,
+            },
+            Recipient {
+                value: 2500,
+                weight: 172,
+            },
+        ];
+        let options = setup_options().with_recipients(40, &recipients);
+        assert_eq!(options.target_value, 3500);
+        assert_eq!(options.base_weight, 40 + 124 + 172);
+        assert_eq!(options.avg_output_weight, 148);
+
+        // Paying other recipients replaces the outputs instead of adding to them
+        let options = options.with_recipients(40, &recipients[..1]);
+        assert_eq!(options.target_value, 1000);
+        assert_eq!(options.base_weight, 40 + 124);
+        assert_eq!(options.avg_output_weight, 124);
+    }
+
+    #[test]
+    fn test_batch_payouts() {
+        let inputs = setup_basic_output_groups();
+        let options = setup_options();
+        let pending: Vec<Recipient> = [3000, 7000, 2000, 1000]
+            .into_iter()
+            .map(|value| Recipient { value, weight: 124 })
+            .collect();
+
+        // The 7000 sats payout doesn't fit after the first one, the smaller ones behind it do
+        let batch = batch_payouts(&pending, &inputs, &options, 4000).unwrap();
+        assert_eq!(batch.included, vec![0, 2, 3]);
+        assert_eq!(batch.deferred, vec![1]);
+        assert_eq!(batch.options.target_value, 6000);
+        assert!(select_coin(&inputs, &batch.options).is_ok());
+
+        // A tight weight limit leaves room for two inputs only
+        let batch = batch_payouts(&pending, &inputs, &options, 600).unwrap();
+        assert_eq!(batch.included, vec![0, 2]);
+        assert_eq!(batch.deferred, vec![1, 3]);
+
+        // Excluded inputs don't fund the batch
+        let options = CoinSelectionOpt {
+            excluded: vec![0],
+            ..setup_options()
+        };
+        let batch = batch_payouts(&pending, &inputs, &options, 4000).unwrap();
+        assert_eq!(batch.included, vec![0, 3]);
+        assert_eq!(batch.deferred, vec![1, 2]);
+
+        // Every must-spend input weighs on the transaction, even when the first one funds the payout
+        let options = CoinSelectionOpt {
+            must_spend: vec![0, 2],
+            ..setup_options()
+        };
+        let batch = batch_payouts(&pending[3..], &inputs, &options, 450).unwrap();
+        assert_eq!(batch.included, vec![0]);
+        let batch = batch_payouts(&pending[3..], &inputs, &options, 350).unwrap();
+        assert_eq!(batch.deferred, vec![0]);
+
+        // Must-spend inputs missing from the inputs can't fund the batch
+        let options = CoinSelectionOpt {
+            must_spend: vec![3],
+            ..setup_options()
+        };
+        assert_eq!(
+            batch_payouts(&pending, &inputs, &options, 4000).unwrap_err(),
+            SelectionError::MustSpendOutOfRange
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{
+    algorithms::CoinSelectionAlgorithm,
+    bitcoin::feerate_to_sat_per_wu,
+    selectcoin::CoinSelector,
+    types::{CoinSelectionOpt, ExcessStrategy, OutputGroup},
+    utils::calculate_fee,
+};
+use bdk_wallet::{
+    bitcoin::{Amount, FeeRate, Script, TxIn, TxOut},
+    coin_selection::{self, decide_change, CoinSelectionResult, InsufficientFunds},
+    Utxo, WeightedUtxo,
+};
+use rand::RngCore;
+
+/// Runs a [`CoinSelector`] as a BDK [`coin_selection::CoinSelectionAlgorithm`].
+///
+/// The required UTXOs are must-spend inputs, the optional ones are the candidates, and the drain script receives the change.
+/// Every error of the selection is reported to BDK as [`InsufficientFunds`], the only error its coin selection supports.
+#[derive(Debug)]
+pub struct BdkCoinSelection {
+    selector: CoinSelector,
+    long_term_feerate: Option<FeeRate>,
+}
+
+impl BdkCoinSelection {
+    /// Wraps `selector`, whose randomized algorithms are seeded from the rng given by BDK.
+    pub fn new(selector: CoinSelector) -> Self {
+        BdkCoinSelection {
+            selector,
+            long_term_feerate: None,
+        }
+    }
+
+    /// Wraps a single algorithm, such as [`crate::algorithms::bnb::BranchAndBound`].
+    pub fn from_algorithm<A: CoinSelectionAlgorithm + 'static>(algorithm: A) -> Self {
+        BdkCoinSelection::new(CoinSelector::new().add_algorithm(algorithm))
+    }
+
+    /// Sets the long term feerate used to compute the waste, which is the target feerate by default.
+    pub fn long_term_feerate(mut self, long_term_feerate: FeeRate) -> Self {
+        self.long_term_feerate = Some(long_term_feerate);
+        self
+    }
+}
+
+impl Default for BdkCoinSelection {
+    /// Runs all the algorithms of this library, like [`crate::selectcoin::select_coin`].
+    fn default() -> Self {
+        BdkCoinSelection::new(CoinSelector::default())
+    }
+}
+
+/// Returns the [`OutputGroup`] of a BDK UTXO, its weight being the one of an input with its satisfaction.
+///
+/// Confirmed local UTXOs have their confirmation height as creation sequence.
+pub fn output_group(weighted_utxo: &WeightedUtxo) -> OutputGroup {
+    let creation_sequence = match &weighted_utxo.utxo {
+        Utxo::Local(local) => local.chain_position.confirmation_height_upper_bound(),
+        Utxo::Foreign { .. } => None,
+    };
+    OutputGroup {
+        value: weighted_utxo.utxo.txout().value.to_sat(),
+        weight: input_weight(weighted_utxo),
+        input_count: 1,
+        creation_sequence,
+        ancestors: None,
+    }
+}
+
+fn input_weight(weighted_utxo: &WeightedUtxo) -> u64 {
+    (TxIn::default().segwit_weight() + weighted_utxo.satisfaction_weight).to_wu()
+}
+
+impl coin_selection::CoinSelectionAlgorithm for BdkCoinSelection {
+    fn coin_select<R: RngCore>(
+        &self,
+        required_utxos: Vec<WeightedUtxo>,
+        optional_utxos: Vec<WeightedUtxo>,
+        fee_rate: FeeRate,
+        target_amount: Amount,
+        drain_script: &Script,
+        rand: &mut R,
+    ) -> Result<CoinSelectionResult, InsufficientFunds> {
+        let utxos: Vec<WeightedUtxo> = required_utxos
+            .iter()
+            .chain(&optional_utxos)
+            .cloned()
+            .collect();
+        let inputs: Vec<OutputGroup> = utxos.iter().map(output_group).collect();
+        let insufficient_funds = || InsufficientFunds {
+            needed: target_amount,
+            available: utxos.iter().map(|utxo| utxo.utxo.txout().value).sum(),
+        };
+
+        let target_feerate = feerate_to_sat_per_wu(fee_rate);
+        let long_term_feerate = self
+            .long_term_feerate
+            .map_or(target_feerate, feerate_to_sat_per_wu);
+        let change_weight = TxOut {
+            value: Amount::ZERO,
+            script_pubkey: drain_script.into(),
+        }
+        .weight()
+        .to_wu();
+        let avg_input_weight = match inputs.len() {
+            0 => 0,
+            count => inputs.iter().map(|input| input.weight).sum::<u64>() / count as u64,
+        };
+        // Creating the change now and spending it later
+        let change_cost = match (
+            calculate_fee(change_weight, target_feerate),
+            calculate_fee(avg_input_weight, long_term_feerate),
+        ) {
+            (Ok(creation_fee), Ok(spending_fee)) => creation_fee + spending_fee,
+            _ => return Err(insufficient_funds()),
+        };
+        let options = CoinSelectionOpt {
+            // The target already pays for the outputs and the transaction header
+            target_value: target_amount.to_sat(),
+            target_feerate,
+            long_term_feerate: Some(long_term_feerate),
+            min_absolute_fee: 0,
+            base_weight: 0,
+            change_weight,
+            change_cost,
+            avg_input_weight,
+            avg_output_weight: change_weight,
+            min_change_value: drain_script.minimal_non_dust().to_sat(),
+            excess_strategy: ExcessStrategy::ToChange,
+            must_spend: (0..required_utxos.len()).collect(),
+            excluded: vec![],
+        };
+
+        let selection_output = self
+            .selector
+            .select_with_seed(&inputs, &options, rand.next_u64())
+            .map_err(|_| insufficient_funds())?;
+
+        // The fee and change are computed the way BDK does, from the fee of the selected inputs
+        let selected: Vec<Utxo> = selection_output
+            .selected_inputs
+            .iter()
+            .map(|&index| utxos[index].utxo.clone())
+            .collect();
+        let fee_amount: Amount = selection_output
+            .selected_inputs
+            .iter()
+            .map(|&index| {
+                fee_rate * (TxIn::default().segwit_weight() + utxos[index].satisfaction_weight)
+            })
+            .sum();
+        let selected_amount: Amount = selected.iter().map(|utxo| utxo.txout().value).sum();
+        let remaining_amount = selected_amount
+            .checked_sub(target_amount + fee_amount)
+            .ok_or_else(insufficient_funds)?;
+
+        Ok(CoinSelectionResult {
+            selected,
+            fee_amount,
+            excess: decide_change(remaining_amount, fee_rate, drain_script),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        algorithms::fifo::Fifo,
+        bdk::{output_group, BdkCoinSelection},
+    };
+    use bdk_wallet::{
+        bitcoin::{
+            hashes::Hash, psbt, Amount, FeeRate, OutPoint, ScriptBuf, Sequence, TxOut, Txid,
+            WPubkeyHash, Weight,
+        },
+        coin_selection::{CoinSelectionAlgorithm, Excess},
+        Utxo, WeightedUtxo,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn setup_utxos(values: &[u64]) -> Vec<WeightedUtxo> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(vout, &value)| WeightedUtxo {
+                // Signature and compressed public key of a P2WPKH input
+                satisfaction_weight: Weight::from_wu(107),
+                utxo: Utxo::Foreign {
+                    outpoint: OutPoint::new(Txid::all_zeros(), vout as u32),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    psbt_input: Box::new(psbt::Input {
+                        witness_utxo: Some(TxOut {
+                            value: Amount::from_sat(value),
+                            script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()),
+                        }),
+                        ..Default::default()
+                    }),
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_output_group() {
+        let utxos = setup_utxos(&[50_000]);
+        let group = output_group(&utxos[0]);
+        assert_eq!(group.value, 50_000);
+        assert_eq!(group.weight, 272);
+        assert_eq!(group.creation_sequence, None);
+    }
+
+    #[test]
+    fn test_bdk_coin_select() {
+        let drain_script = ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros());
+        let fee_rate = FeeRate::from_sat_per_vb(5).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // Required UTXOs are always spent
+        let required = setup_utxos(&[10_000]);
+        let optional = setup_utxos(&[100_000, 200_000, 300_000]);
+        let result = BdkCoinSelection::default()
+            .coin_select(
+                required.clone(),
+                optional.clone(),
+                fee_rate,
+                Amount::from_sat(150_000),
+                &drain_script,
+                &mut rng,
+            )
+            .unwrap();
+        assert!(result.selected.contains(&required[0].utxo));
+        // Each P2WPKH input pays 68 vbytes
+        assert_eq!(
+            result.fee_amount,
+            Amount::from_sat(340 * result.selected.len() as u64)
+        );
+        let remaining = result.selected_amount() - Amount::from_sat(150_000) - result.fee_amount;
+        match result.excess {
+            Excess::Change { amount, fee } => assert_eq!(amount + fee, remaining),
+            Excess::NoChange {
+                remaining_amount, ..
+            } => assert_eq!(remaining_amount, remaining),
+        }
+
+        // A single algorithm can be wrapped
+        let result = BdkCoinSelection::from_algorithm(Fifo)
+            .coin_select(
+                vec![],
+                optional.clone(),
+                fee_rate,
+                Amount::from_sat(150_000),
+                &drain_script,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(result.selected_amount(), Amount::from_sat(300_000));
+
+        // Selection errors are reported as insufficient funds
+        let error = BdkCoinSelection::default()
+            .coin_select(
+                required,
+                optional,
+                fee_rate,
+                Amount::from_sat(1_000_000),
+                &drain_script,
+                &mut rng,
+            )
+            .unwrap_err();
+        assert_eq!(error.needed, Amount::from_sat(1_000_000));
+        assert_eq!(error.available, Amount::from_sat(610_000));
+    }
+}
@@ -0,0 +1,421 @@
+use rust_coinselect::{
+    selectcoin::CoinSelector,
+    types::{CoinSelectionOpt, OutputGroup, SelectionReport},
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::{
+    env, fs,
+    io::{self, Read},
+    process::ExitCode,
+    time::Duration,
+};
+
+const USAGE: &str = "\
+Usage: coinselect [OPTIONS]
+
+Selects coins among the candidates and prints the selection of each algorithm.
+
+Options:
+  -c, --candidates <PATH>  Candidates, as a JSON array of output groups or a CSV file with the
+                           value,weight,input_count,creation_sequence columns
+  -o, --options <PATH>     Selection options, as a JSON object or a CSV file of field,value rows
+  -a, --algorithm <NAME>   all (default, like select_coin), bnb, coingrinder, fifo, knapsack,
+                           lowestlarger or srd
+  -s, --seed <SEED>        Seeds the randomized algorithms, to replay a selection
+  -j, --json               Prints JSON instead of a table
+  -h, --help               Prints this help
+
+A PATH of - reads standard input. Without --candidates and --options, standard input is a JSON
+object with the \"candidates\" and \"options\" fields.";
+
+/// Parsed command line arguments.
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    candidates: Option<String>,
+    options: Option<String>,
+    algorithm: Option<String>,
+    seed: Option<u64>,
+    json: bool,
+    help: bool,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "-c" | "--candidates" => parsed.candidates = Some(value()?),
+            "-o" | "--options" => parsed.options = Some(value()?),
+            "-a" | "--algorithm" => {
+                let name = value()?;
+                if name != "all" && !CoinSelector::default().algorithm_names().contains(&&*name) {
+                    return Err(format!("Unknown algorithm {}", name));
+                }
+                parsed.algorithm = Some(name).filter(|name| name != "all");
+            }
+            "-s" | "--seed" => {
+                let seed = value()?;
+                parsed.seed = Some(seed.parse().map_err(|_| format!("Invalid seed {}", seed))?);
+            }
+            "-j" | "--json" => parsed.json = true,
+            "-h" | "--help" => parsed.help = true,
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+    if parsed.candidates.as_deref() == Some("-") && parsed.options.as_deref() == Some("-") {
+        return Err("Only one of the candidates and options can be read from stdin".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Reads the file at `path`, or stdin for `-`.
+fn read_source(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|error| format!("Can't read stdin: {}", error))?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path).map_err(|error| format!("Can't read {}: {}", path, error))
+    }
+}
+
+/// Whether `text` is a JSON document rather than CSV.
+fn is_json(text: &str) -> bool {
+    matches!(text.trim_start().chars().next(), Some('[') | Some('{'))
+}
+
+fn parse_candidates(text: &str) -> Result<Vec<OutputGroup>, String> {
+    if is_json(text) {
+        serde_json::from_str(text).map_err(|error| format!("Invalid candidates: {}", error))
+    } else {
+        csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|error| format!("Invalid candidates: {}", error))
+    }
+}
+
+/// Parses the options from JSON, or from CSV rows of a field name and its value.
+///
+/// CSV values are read as JSON when they can be, such as numbers and `[0, 2]` index lists, and as strings otherwise.
+fn parse_options(text: &str) -> Result<CoinSelectionOpt, String> {
+    let value = if is_json(text) {
+        serde_json::from_str(text).map_err(|error| format!("Invalid options: {}", error))?
+    } else {
+        let mut fields = Map::new();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes());
+        for record in reader.records() {
+            let record = record.map_err(|error| format!("Invalid options: {}", error))?;
+            match (record.get(0), record.get(1)) {
+                (Some("field"), Some("value")) => {}
+                (Some(field), Some(value)) => {
+                    let value = serde_json::from_str(value)
+                        .unwrap_or_else(|_| Value::String(value.to_string()));
+                    fields.insert(field.to_string(), value);
+                }
+                _ => return Err(format!("Invalid options row {:?}", record)),
+            }
+        }
+        Value::Object(fields)
+    };
+    serde_json::from_value(value).map_err(|error| format!("Invalid options: {}", error))
+}
+
+/// Candidates and options read from stdin as a single JSON document.
+#[derive(Deserialize)]
+struct Request {
+    candidates: Vec<OutputGroup>,
+    options: CoinSelectionOpt,
+}
+
+fn load(args: &Args) -> Result<(Vec<OutputGroup>, CoinSelectionOpt), String> {
+    match (&args.candidates, &args.options) {
+        (Some(candidates), Some(options)) => Ok((
+            parse_candidates(&read_source(candidates)?)?,
+            parse_options(&read_source(options)?)?,
+        )),
+        (None, None) => {
+            let request: Request = serde_json::from_str(&read_source("-")?)
+                .map_err(|error| format!("Invalid input: {}", error))?;
+            Ok((request.candidates, request.options))
+        }
+        _ => Err("Both --candidates and --options are required".to_string()),
+    }
+}
+
+/// Runs the algorithms of `select_coin`, or only `algorithm` if given, the randomized ones seeded from `seed` if given.
+fn run(
+    algorithm: Option<&str>,
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    seed: Option<u64>,
+) -> SelectionReport {
+    let mut selector = CoinSelector::default();
+    if let Some(algorithm) = algorithm {
+        let others: Vec<String> = selector
+            .algorithm_names()
+            .into_iter()
+            .filter(|&name| name != algorithm)
+            .map(String::from)
+            .collect();
+        for name in others {
+            selector = selector.remove_algorithm(&name);
+        }
+    }
+    match seed {
+        Some(seed) => selector.report_with_seed(inputs, options, seed),
+        None => selector.report(inputs, options),
+    }
+}
+
+fn format_inputs(selected_inputs: &[usize]) -> String {
+    selected_inputs
+        .iter()
+        .map(|index| index.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn format_change(change_value: Option<u64>) -> String {
+    change_value.map_or("none".to_string(), |change| change.to_string())
+}
+
+fn format_duration(duration: Option<Duration>) -> String {
+    duration.map_or("-".to_string(), |duration| duration.as_micros().to_string())
+}
+
+fn render_table(report: &SelectionReport) -> String {
+    let mut table = String::new();
+    match report.best() {
+        Ok(selection) => {
+            table += &format!("Selected by {}\n", selection.algorithm);
+            table += &format!("  inputs  {}\n", format_inputs(&selection.selected_inputs));
+            table += &format!("  fee     {}\n", selection.fee);
+            table += &format!("  change  {}\n", format_change(selection.change_value));
+            table += &format!("  excess  {}\n", selection.excess);
+            table += &format!("  waste   {}\n", selection.waste.0);
+        }
+        Err(error) => table += &format!("No selection: {}\n", error),
+    }
+    table += &format!(
+        "\n{:<14}{:>10}  {:<20}{:>10}{:>12}{:>10}\n",
+        "algorithm", "time (us)", "inputs", "fee", "change", "waste"
+    );
+    for outcome in &report.outcomes {
+        let name = &outcome.algorithm;
+        let duration = format_duration(outcome.duration);
+        table += &match &outcome.result {
+            Ok(selection) => format!(
+                "{:<14}{:>10}  {:<20}{:>10}{:>12}{:>10}\n",
+                name,
+                duration,
+                format_inputs(&selection.selected_inputs),
+                selection.fee,
+                format_change(selection.change_value),
+                selection.waste.0
+            ),
+            Err(error) => format!("{:<14}{:>10}  {}\n", name, duration, error),
+        };
+    }
+    table
+}
+
+fn render_json(report: &SelectionReport) -> String {
+    let comparison: Vec<Value> = report
+        .outcomes
+        .iter()
+        .map(|outcome| {
+            let duration_us = outcome.duration.map(|duration| duration.as_micros() as u64);
+            match &outcome.result {
+                Ok(selection) => json!({
+                    "algorithm": outcome.algorithm,
+                    "duration_us": duration_us,
+                    "selection": selection,
+                }),
+                Err(error) => json!({
+                    "algorithm": outcome.algorithm,
+                    "duration_us": duration_us,
+                    "error": error.to_string(),
+                }),
+            }
+        })
+        .collect();
+    let document = match report.best() {
+        Ok(selection) => json!({ "selection": selection, "comparison": comparison }),
+        Err(error) => json!({ "error": error.to_string(), "comparison": comparison }),
+    };
+    serde_json::to_string_pretty(&document).expect("Selections serialize to JSON")
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if args.help {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let (inputs, options) = match load(&args) {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::from(2);
+        }
+    };
+
+    let report = run(args.algorithm.as_deref(), &inputs, &options, args.seed);
+    if args.json {
+        println!("{}", render_json(&report));
+    } else {
+        print!("{}", render_table(&report));
+    }
+    match report.best() {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use rust_coinselect::types::ExcessStrategy;
+
+    fn setup_inputs() -> Vec<OutputGroup> {
+        parse_candidates(
+            "value,weight,input_count,creation_sequence\n\
+             100000,272,1,2\n\
+             200000,272,1,\n\
+             300000,272,1,0\n",
+        )
+        .unwrap()
+    }
+
+    fn setup_options() -> CoinSelectionOpt {
+        parse_options(
+            "field,value\n\
+             target_value,150000\n\
+             target_feerate,0.5\n\
+             long_term_feerate,0.4\n\
+             min_absolute_fee,0\n\
+             base_weight,40\n\
+             change_weight,124\n\
+             change_cost,100\n\
+             avg_input_weight,272\n\
+             avg_output_weight,124\n\
+             min_change_value,500\n\
+             excess_strategy,ToChange\n\
+             excluded,\"[2]\"\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse_args(
+            [
+                "-c",
+                "candidates.csv",
+                "--options",
+                "-",
+                "-a",
+                "fifo",
+                "--seed",
+                "7",
+                "-j",
+            ]
+            .into_iter()
+            .map(String::from),
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            Args {
+                candidates: Some("candidates.csv".to_string()),
+                options: Some("-".to_string()),
+                algorithm: Some("fifo".to_string()),
+                seed: Some(7),
+                json: true,
+                help: false,
+            }
+        );
+        let args = parse_args(["-a", "all"].into_iter().map(String::from)).unwrap();
+        assert_eq!(args.algorithm, None);
+        assert!(parse_args(["-a", "greedy"].into_iter().map(String::from)).is_err());
+        assert!(parse_args(["--seed"].into_iter().map(String::from)).is_err());
+        assert!(parse_args(["-c", "-", "-o", "-"].into_iter().map(String::from)).is_err());
+    }
+
+    #[test]
+    fn test_parse_inputs() {
+        let inputs = setup_inputs();
+        assert_eq!(inputs.len(), 3);
+        assert_eq!(inputs[0].creation_sequence, Some(2));
+        assert_eq!(inputs[1].creation_sequence, None);
+        assert_eq!(
+            parse_candidates(r#"[{"value": 1000, "weight": 272, "input_count": 1}]"#).unwrap()[0]
+                .value,
+            1000
+        );
+
+        let options = setup_options();
+        assert_eq!(options.excess_strategy, ExcessStrategy::ToChange);
+        assert_eq!(options.long_term_feerate, Some(0.4));
+        assert_eq!(options.excluded, vec![2]);
+        assert!(options.must_spend.is_empty());
+        assert!(parse_options("target_value,1000\n").is_err());
+    }
+
+    #[test]
+    fn test_comparison() {
+        let inputs = setup_inputs();
+        let options = setup_options();
+        let report = run(None, &inputs, &options, Some(7));
+        assert_eq!(report.outcomes.len(), 6);
+        let best = report.best().unwrap();
+        // Seeded runs are replayed exactly
+        assert_eq!(run(None, &inputs, &options, Some(7)).best(), Ok(best));
+        let report_fifo = run(Some("fifo"), &inputs, &options, None);
+        assert_eq!(report_fifo.outcomes.len(), 1);
+        assert_eq!(report_fifo.outcomes[0].algorithm, "fifo");
+
+        let table = render_table(&report);
+        assert!(table.starts_with(&format!("Selected by {}", best.algorithm)));
+        assert!(table.contains("  0,1 "));
+        let json: Value = serde_json::from_str(&render_json(&report)).unwrap();
+        assert_eq!(json["selection"]["algorithm"], best.algorithm.as_str());
+        assert_eq!(json["comparison"].as_array().unwrap().len(), 6);
+        assert!(json["comparison"][0]["duration_us"].is_u64());
+
+        let report = run(
+            Some("fifo"),
+            &inputs,
+            &CoinSelectionOpt {
+                target_value: 1_000_000,
+                ..options
+            },
+            None,
+        );
+        assert!(render_table(&report).starts_with("No selection"));
+        let json: Value = serde_json::from_str(&render_json(&report)).unwrap();
+        assert_eq!(
+            json["comparison"][0]["error"],
+            "The Inputs funds are insufficient"
+        );
+    }
+}
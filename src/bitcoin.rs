@@ -0,0 +1,306 @@
+use crate::{
+    types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, Recipient},
+    utils::{calculate_base_weight_btc, calculate_fee},
+    weight,
+};
+use ::bitcoin::{Amount, FeeRate, OutPoint, Script, ScriptBuf, TxOut, Weight};
+
+/// Script type of a UTXO, which determines the weight of the input spending it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScriptType {
+    /// Pay to public key hash, spent with a compressed public key.
+    P2pkh,
+    /// Pay to witness public key hash nested in pay to script hash.
+    P2shP2wpkh,
+    /// Pay to witness public key hash.
+    P2wpkh,
+    /// Pay to taproot, spent through the key path.
+    P2tr,
+}
+
+impl ScriptType {
+    /// Returns the script type of `script_pubkey`, or `None` if it isn't one of the supported types.
+    ///
+    /// A pay to script hash output doesn't tell which script it nests, so `None` is returned for it as well.
+    /// Use [`ScriptType::from_redeem_script`] when the redeem script is known.
+    pub fn from_script_pubkey(script_pubkey: &Script) -> Option<Self> {
+        if script_pubkey.is_p2pkh() {
+            Some(ScriptType::P2pkh)
+        } else if script_pubkey.is_p2wpkh() {
+            Some(ScriptType::P2wpkh)
+        } else if script_pubkey.is_p2tr() {
+            Some(ScriptType::P2tr)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the script type of the pay to script hash `script_pubkey` nesting `redeem_script`.
+    ///
+    /// Returns `None` if `script_pubkey` doesn't commit to `redeem_script`, or if the nested script isn't supported.
+    pub fn from_redeem_script(script_pubkey: &Script, redeem_script: &Script) -> Option<Self> {
+        if !script_pubkey.is_p2sh()
+            || *script_pubkey != *ScriptBuf::new_p2sh(&redeem_script.script_hash())
+        {
+            None
+        } else if redeem_script.is_p2wpkh() {
+            Some(ScriptType::P2shP2wpkh)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the weight of an input spending this script type, including its outpoint, sequence and satisfaction.
+    ///
+    /// Signatures are assumed to have their largest possible size, see [`crate::weight`].
+    pub fn input_weight(&self) -> u64 {
+        match self {
+            ScriptType::P2pkh => weight::p2pkh_input_weight(),
+            ScriptType::P2shP2wpkh => weight::p2sh_p2wpkh_input_weight(),
+            ScriptType::P2wpkh => weight::p2wpkh_input_weight(),
+            ScriptType::P2tr => weight::p2tr_key_path_input_weight(),
+        }
+    }
+}
+
+impl OutputGroup {
+    /// Creates the [`OutputGroup`] of a single UTXO spent as `script_type`.
+    pub fn from_txout(txout: &TxOut, script_type: ScriptType) -> Self {
+        OutputGroup {
+            value: txout.value.to_sat(),
+            weight: script_type.input_weight(),
+            input_count: 1,
+            creation_sequence: None,
+            ancestors: None,
+        }
+    }
+
+    /// Creates the [`OutputGroup`] of a single UTXO spent through `descriptor`.
+    ///
+    /// The weight is the largest satisfaction weight of the descriptor, and an error is returned if it can't be satisfied.
+    #[cfg(feature = "miniscript")]
+    pub fn from_txout_with_descriptor<Pk: miniscript::MiniscriptKey>(
+        txout: &TxOut,
+        descriptor: &miniscript::Descriptor<Pk>,
+    ) -> Result<Self, miniscript::Error> {
+        // An unsatisfied input has an empty script sig and an empty witness
+        let unsatisfied_weight = weight::TXIN_BASE_WEIGHT + 4 + 1;
+        Ok(OutputGroup {
+            value: txout.value.to_sat(),
+            weight: unsatisfied_weight + descriptor.max_weight_to_satisfy()?.to_wu(),
+            input_count: 1,
+            creation_sequence: None,
+            ancestors: None,
+        })
+    }
+}
+
+/// Creates one [`OutputGroup`] per UTXO, with the script type detected from its `script_pubkey`.
+///
+/// The groups are in the order of `utxos`, so the indices of a [`crate::types::SelectionOutput`] refer to `utxos`.
+/// Returns `None` if the script type of a UTXO isn't supported, see [`ScriptType::from_script_pubkey`].
+pub fn output_groups(utxos: &[(OutPoint, TxOut)]) -> Option<Vec<OutputGroup>> {
+    utxos
+        .iter()
+        .map(|(_, txout)| {
+            ScriptType::from_script_pubkey(&txout.script_pubkey)
+                .map(|script_type| OutputGroup::from_txout(txout, script_type))
+        })
+        .collect()
+}
+
+impl From<&TxOut> for Recipient {
+    fn from(txout: &TxOut) -> Self {
+        Recipient {
+            value: txout.value.to_sat(),
+            weight: txout.weight().to_wu(),
+        }
+    }
+}
+
+/// Converts a [`FeeRate`] to sats per weight unit, the unit of [`CoinSelectionOpt::target_feerate`].
+pub fn feerate_to_sat_per_wu(feerate: FeeRate) -> f32 {
+    feerate.to_sat_per_kwu() as f32 / 1000.0
+}
+
+impl CoinSelectionOpt {
+    /// Creates the options of a segwit transaction paying `recipients` at `feerate`, with change sent to `change_script`.
+    ///
+    /// The change must be worth at least the dust limit of `change_script`, and its cost includes spending it later at
+    /// `long_term_feerate`. The average input weight is the one of the change script type, or of P2WPKH if it isn't
+    /// supported. Returns `None` if `feerate` or `long_term_feerate` is zero.
+    pub fn from_bitcoin(
+        recipients: &[TxOut],
+        change_script: &Script,
+        feerate: FeeRate,
+        long_term_feerate: FeeRate,
+    ) -> Option<Self> {
+        let target_feerate = feerate_to_sat_per_wu(feerate);
+        let long_term_feerate = feerate_to_sat_per_wu(long_term_feerate);
+        let change_weight = TxOut {
+            value: Amount::ZERO,
+            script_pubkey: change_script.into(),
+        }
+        .weight()
+        .to_wu();
+        let change_input_weight = ScriptType::from_script_pubkey(change_script)
+            .unwrap_or(ScriptType::P2wpkh)
+            .input_weight();
+        let change_cost = calculate_fee(change_weight, target_feerate).ok()?
+            + calculate_fee(change_input_weight, long_term_feerate).ok()?;
+
+        let recipients: Vec<Recipient> = recipients.iter().map(Recipient::from).collect();
+        let base_weight = calculate_base_weight_btc(0);
+        let options = CoinSelectionOpt {
+            target_value: 0,
+            target_feerate,
+            long_term_feerate: Some(long_term_feerate),
+            min_absolute_fee: 0,
+            base_weight,
+            change_weight,
+            change_cost,
+            avg_input_weight: change_input_weight,
+            avg_output_weight: change_weight,
+            min_change_value: change_script.minimal_non_dust().to_sat(),
+            excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
+        };
+        Some(options.with_recipients(base_weight, &recipients))
+    }
+
+    /// Returns the target feerate as a [`FeeRate`], rounded down to the sat per kilo weight unit.
+    pub fn bitcoin_feerate(&self) -> FeeRate {
+        FeeRate::from_sat_per_kwu((self.target_feerate * 1000.0) as u64)
+    }
+
+    /// Returns the target value as an [`Amount`].
+    pub fn target_amount(&self) -> Amount {
+        Amount::from_sat(self.target_value)
+    }
+
+    /// Returns the base weight as a [`Weight`].
+    pub fn bitcoin_base_weight(&self) -> Weight {
+        Weight::from_wu(self.base_weight)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        bitcoin::{feerate_to_sat_per_wu, output_groups, ScriptType},
+        selectcoin::select_coin,
+        types::CoinSelectionOpt,
+    };
+    use ::bitcoin::{hashes::Hash, Amount, FeeRate, OutPoint, ScriptBuf, TxOut, Txid, WPubkeyHash};
+
+    fn setup_utxos() -> Vec<(OutPoint, TxOut)> {
+        [100_000, 250_000, 50_000]
+            .into_iter()
+            .enumerate()
+            .map(|(vout, value)| {
+                (
+                    OutPoint::new(Txid::all_zeros(), vout as u32),
+                    TxOut {
+                        value: Amount::from_sat(value),
+                        script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_input_weights() {
+        // Largest signatures: 72 byte DER signature with the sighash flag, 65 byte Schnorr signature with a sighash flag
+        assert_eq!(ScriptType::P2wpkh.input_weight(), 272);
+        assert_eq!(ScriptType::P2shP2wpkh.input_weight(), 364);
+        assert_eq!(ScriptType::P2pkh.input_weight(), 593);
+        assert_eq!(ScriptType::P2tr.input_weight(), 231);
+
+        let utxos = setup_utxos();
+        let groups = output_groups(&utxos).unwrap();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[1].value, 250_000);
+        assert_eq!(groups[1].weight, 272);
+
+        // Unsupported script types can't be converted
+        let mut utxos = utxos;
+        utxos[0].1.script_pubkey = ScriptBuf::new();
+        assert!(output_groups(&utxos).is_none());
+
+        // A pay to script hash output is only supported along with its redeem script
+        let redeem_script = ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros());
+        let script_pubkey = ScriptBuf::new_p2sh(&redeem_script.script_hash());
+        assert_eq!(ScriptType::from_script_pubkey(&script_pubkey), None);
+        assert_eq!(
+            ScriptType::from_redeem_script(&script_pubkey, &redeem_script),
+            Some(ScriptType::P2shP2wpkh)
+        );
+        assert_eq!(
+            ScriptType::from_redeem_script(&script_pubkey, &ScriptBuf::new()),
+            None
+        );
+        let unsupported = ScriptBuf::new_p2sh(&ScriptBuf::new().script_hash());
+        assert_eq!(
+            ScriptType::from_redeem_script(&unsupported, &ScriptBuf::new()),
+            None
+        );
+    }
+
+    #[cfg(feature = "miniscript")]
+    #[test]
+    fn test_descriptor_input_weight() {
+        use crate::types::OutputGroup;
+        use miniscript::{Descriptor, DescriptorPublicKey};
+        use std::str::FromStr;
+
+        let descriptor = Descriptor::<DescriptorPublicKey>::from_str(
+            "wpkh(02e96fe52ef0e22d2f131dd425ce1893073a3c6ad20e8cac36726393dfb4856a4c)",
+        )
+        .unwrap();
+        let (_, txout) = &setup_utxos()[0];
+        let group = OutputGroup::from_txout_with_descriptor(txout, &descriptor).unwrap();
+        assert_eq!(group.weight, ScriptType::P2wpkh.input_weight());
+    }
+
+    #[test]
+    fn test_options_from_bitcoin() {
+        let utxos = setup_utxos();
+        let recipient = TxOut {
+            value: Amount::from_sat(120_000),
+            script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()),
+        };
+        let change_script = ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros());
+        let feerate = FeeRate::from_sat_per_vb(10).unwrap();
+        let options = CoinSelectionOpt::from_bitcoin(
+            &[recipient],
+            &change_script,
+            feerate,
+            FeeRate::from_sat_per_vb(5).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(options.target_value, 120_000);
+        assert_eq!(options.target_feerate, 2.5);
+        assert_eq!(feerate_to_sat_per_wu(feerate), 2.5);
+        assert_eq!(options.bitcoin_feerate(), feerate);
+        assert_eq!(options.target_amount(), Amount::from_sat(120_000));
+        // A P2WPKH output is 31 bytes
+        assert_eq!(options.change_weight, 124);
+        assert_eq!(options.base_weight, 43 + 124);
+        assert_eq!(options.min_change_value, 294);
+
+        let groups = output_groups(&utxos).unwrap();
+        let selection_output = select_coin(&groups, &options).unwrap();
+        assert!(selection_output.total_value >= 120_000 + selection_output.fee);
+
+        // A zero feerate is rejected
+        assert!(
+            CoinSelectionOpt::from_bitcoin(&[], &change_script, FeeRate::ZERO, FeeRate::ZERO)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,547 @@
+use crate::{
+    algorithms::{
+        bnb::select_coin_bnb, coingrinder::select_coin_coingrinder, fifo::select_coin_fifo,
+        knapsack::select_coin_knapsack, lowestlarger::select_coin_lowestlarger,
+        srd::select_coin_srd,
+    },
+    selectcoin::select_coin,
+    types::{
+        Ancestors, CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError, SelectionOutput,
+    },
+};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+/// Status returned by every function, `COINSELECT_STATUS_OK` on success.
+///
+/// The codes are stable: new errors get new codes, existing codes are never reused.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoinselectStatus {
+    /// The selection succeeded.
+    Ok = 0,
+    /// See [`SelectionError::NonPositiveFeeRate`].
+    NonPositiveFeeRate = 1,
+    /// See [`SelectionError::AbnormallyHighFeeRate`].
+    AbnormallyHighFeeRate = 2,
+    /// See [`SelectionError::InsufficientFunds`].
+    InsufficientFunds = 3,
+    /// See [`SelectionError::NoSolutionFound`].
+    NoSolutionFound = 4,
+    /// See [`SelectionError::InsufficientReplacementFee`].
+    InsufficientReplacementFee = 5,
+    /// See [`SelectionError::MustSpendOutOfRange`].
+    MustSpendOutOfRange = 6,
+    /// See [`SelectionError::InputReserved`].
+    InputReserved = 7,
+    /// A required pointer is null, or an argument isn't one of its allowed values.
+    InvalidArgument = -1,
+    /// The library panicked, which is a bug. `out` isn't written.
+    Panic = -2,
+}
+
+impl From<SelectionError> for CoinselectStatus {
+    fn from(error: SelectionError) -> Self {
+        match error {
+            SelectionError::NonPositiveFeeRate => CoinselectStatus::NonPositiveFeeRate,
+            SelectionError::AbnormallyHighFeeRate => CoinselectStatus::AbnormallyHighFeeRate,
+            SelectionError::InsufficientFunds => CoinselectStatus::InsufficientFunds,
+            SelectionError::NoSolutionFound => CoinselectStatus::NoSolutionFound,
+            SelectionError::InsufficientReplacementFee => {
+                CoinselectStatus::InsufficientReplacementFee
+            }
+            SelectionError::MustSpendOutOfRange => CoinselectStatus::MustSpendOutOfRange,
+            SelectionError::InputReserved => CoinselectStatus::InputReserved,
+        }
+    }
+}
+
+/// See [`ExcessStrategy`], the values of [`CoinselectOptions::excess_strategy`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoinselectExcessStrategy {
+    /// See [`ExcessStrategy::ToFee`].
+    ToFee = 0,
+    /// See [`ExcessStrategy::ToRecipient`].
+    ToRecipient = 1,
+    /// See [`ExcessStrategy::ToChange`].
+    ToChange = 2,
+}
+
+impl From<CoinselectExcessStrategy> for ExcessStrategy {
+    fn from(strategy: CoinselectExcessStrategy) -> Self {
+        match strategy {
+            CoinselectExcessStrategy::ToFee => ExcessStrategy::ToFee,
+            CoinselectExcessStrategy::ToRecipient => ExcessStrategy::ToRecipient,
+            CoinselectExcessStrategy::ToChange => ExcessStrategy::ToChange,
+        }
+    }
+}
+
+impl CoinselectExcessStrategy {
+    /// Returns the strategy of value `value`, or `None` if it isn't one of the variants.
+    fn from_value(value: u32) -> Option<Self> {
+        [
+            CoinselectExcessStrategy::ToFee,
+            CoinselectExcessStrategy::ToRecipient,
+            CoinselectExcessStrategy::ToChange,
+        ]
+        .into_iter()
+        .find(|&strategy| strategy as u32 == value)
+    }
+}
+
+/// An input candidate, see [`OutputGroup`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoinselectOutputGroup {
+    /// Total value of the UTXO(s).
+    pub value: u64,
+    /// Total weight of spending the UTXO(s).
+    pub weight: u64,
+    /// The total number of inputs.
+    pub input_count: usize,
+    /// Relative creation sequence, read only if `has_creation_sequence` is set.
+    pub creation_sequence: u32,
+    /// Whether the group has a creation sequence, used only for FIFO selection.
+    pub has_creation_sequence: bool,
+    /// Total weight of the unconfirmed ancestors, read only if `has_ancestors` is set.
+    pub ancestor_weight: u64,
+    /// Total fee paid by the unconfirmed ancestors, read only if `has_ancestors` is set.
+    pub ancestor_fee: u64,
+    /// Whether the group has unconfirmed ancestors, see [`Ancestors`].
+    pub has_ancestors: bool,
+}
+
+impl From<&CoinselectOutputGroup> for OutputGroup {
+    fn from(group: &CoinselectOutputGroup) -> Self {
+        OutputGroup {
+            value: group.value,
+            weight: group.weight,
+            input_count: group.input_count,
+            creation_sequence: group
+                .has_creation_sequence
+                .then_some(group.creation_sequence),
+            ancestors: group.has_ancestors.then_some(Ancestors {
+                weight: group.ancestor_weight,
+                fee: group.ancestor_fee,
+            }),
+        }
+    }
+}
+
+/// The selection options, see [`CoinSelectionOpt`].
+///
+/// `must_spend` and `excluded` point to `must_spend_len` and `excluded_len` input indices, and may be null when empty.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CoinselectOptions {
+    /// The value to select.
+    pub target_value: u64,
+    /// The target feerate in sats per weight unit.
+    pub target_feerate: f32,
+    /// The long term feerate in sats per weight unit, read only if `has_long_term_feerate` is set.
+    pub long_term_feerate: f32,
+    /// Whether the long term feerate is set.
+    pub has_long_term_feerate: bool,
+    /// Lowest possible transaction fee.
+    pub min_absolute_fee: u64,
+    /// Weight of the transaction other than the selected inputs.
+    pub base_weight: u64,
+    /// Weight added by a change output.
+    pub change_weight: u64,
+    /// Cost of creating and later spending a change output.
+    pub change_cost: u64,
+    /// Estimate of the average weight of an input.
+    pub avg_input_weight: u64,
+    /// Estimate of the average weight of an output.
+    pub avg_output_weight: u64,
+    /// Smallest change value worth creating.
+    pub min_change_value: u64,
+    /// Where the excess of the selection goes, one of the [`CoinselectExcessStrategy`] values.
+    ///
+    /// It is an integer since C doesn't restrict enums to their variants, and other values are rejected as
+    /// `COINSELECT_STATUS_INVALID_ARGUMENT`.
+    pub excess_strategy: u32,
+    /// Indices of the inputs that must be spent.
+    pub must_spend: *const usize,
+    /// Number of indices in `must_spend`.
+    pub must_spend_len: usize,
+    /// Indices of the inputs that must not be selected.
+    pub excluded: *const usize,
+    /// Number of indices in `excluded`.
+    pub excluded_len: usize,
+}
+
+/// A selection, whose `selected_inputs` buffer is owned by the caller and released with [`coinselect_selection_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct CoinselectSelection {
+    /// Indices of the selected inputs.
+    pub selected_inputs: *mut usize,
+    /// Number of indices in `selected_inputs`.
+    pub selected_inputs_len: usize,
+    /// Waste of the selection.
+    pub waste: i64,
+    /// Fee paid by the transaction.
+    pub fee: u64,
+    /// Value of the change output, 0 without change.
+    pub change_value: u64,
+    /// Excess left to the fee or the recipient.
+    pub excess: u64,
+}
+
+impl From<SelectionOutput> for CoinselectSelection {
+    fn from(output: SelectionOutput) -> Self {
+        let selected_inputs_len = output.selected_inputs.len();
+        let selected_inputs =
+            Box::into_raw(output.selected_inputs.into_boxed_slice()) as *mut usize;
+        CoinselectSelection {
+            selected_inputs,
+            selected_inputs_len,
+            waste: output.waste.0,
+            fee: output.fee,
+            change_value: output.change_value.unwrap_or(0),
+            excess: output.excess,
+        }
+    }
+}
+
+/// Returns the `len` values at `data`, or `None` if `data` is null while `len` isn't 0.
+///
+/// # Safety
+///
+/// A non-null `data` must point to `len` initialized values.
+unsafe fn c_slice<'a, T>(data: *const T, len: usize) -> Option<&'a [T]> {
+    match (data.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(slice::from_raw_parts(data, len)),
+    }
+}
+
+/// Converts the C arguments, runs `algorithm` and writes its selection to `out`.
+///
+/// A panic is caught and reported as `CoinselectStatus::Panic`, as unwinding into C is undefined behavior.
+///
+/// # Safety
+///
+/// See [`coinselect_select_coin`].
+unsafe fn select_c(
+    inputs: *const CoinselectOutputGroup,
+    inputs_len: usize,
+    options: *const CoinselectOptions,
+    out: *mut CoinselectSelection,
+    algorithm: fn(&[OutputGroup], &CoinSelectionOpt) -> Result<SelectionOutput, SelectionError>,
+) -> CoinselectStatus {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        if options.is_null() || out.is_null() {
+            return CoinselectStatus::InvalidArgument;
+        }
+        let options = &*options;
+        let (inputs, must_spend, excluded, excess_strategy) = match (
+            c_slice(inputs, inputs_len),
+            c_slice(options.must_spend, options.must_spend_len),
+            c_slice(options.excluded, options.excluded_len),
+            CoinselectExcessStrategy::from_value(options.excess_strategy),
+        ) {
+            (Some(inputs), Some(must_spend), Some(excluded), Some(excess_strategy)) => {
+                (inputs, must_spend, excluded, excess_strategy)
+            }
+            _ => return CoinselectStatus::InvalidArgument,
+        };
+        let inputs: Vec<OutputGroup> = inputs.iter().map(OutputGroup::from).collect();
+        let options = CoinSelectionOpt {
+            target_value: options.target_value,
+            target_feerate: options.target_feerate,
+            long_term_feerate: options
+                .has_long_term_feerate
+                .then_some(options.long_term_feerate),
+            min_absolute_fee: options.min_absolute_fee,
+            base_weight: options.base_weight,
+            change_weight: options.change_weight,
+            change_cost: options.change_cost,
+            avg_input_weight: options.avg_input_weight,
+            avg_output_weight: options.avg_output_weight,
+            min_change_value: options.min_change_value,
+            excess_strategy: excess_strategy.into(),
+            must_spend: must_spend.to_vec(),
+            excluded: excluded.to_vec(),
+        };
+        match algorithm(&inputs, &options) {
+            Ok(selection_output) => {
+                ptr::write(out, selection_output.into());
+                CoinselectStatus::Ok
+            }
+            Err(error) => error.into(),
+        }
+    }))
+    .unwrap_or(CoinselectStatus::Panic)
+}
+
+/// Runs every algorithm and writes the selection with the least waste to `out`, see [`select_coin`].
+///
+/// `out` is only written on success, and its buffer must then be released with [`coinselect_selection_free`].
+///
+/// # Safety
+///
+/// `inputs` must point to `inputs_len` groups (it may be null if `inputs_len` is 0), `options` to valid options whose
+/// index arrays hold their stated lengths, and `out` to writable memory for a [`CoinselectSelection`].
+#[no_mangle]
+pub unsafe extern "C" fn coinselect_select_coin(
+    inputs: *const CoinselectOutputGroup,
+    inputs_len: usize,
+    options: *const CoinselectOptions,
+    out: *mut CoinselectSelection,
+) -> CoinselectStatus {
+    select_c(inputs, inputs_len, options, out, select_coin)
+}
+
+/// Selects with Branch and Bound, see [`coinselect_select_coin`] and [`select_coin_bnb`].
+///
+/// # Safety
+///
+/// See [`coinselect_select_coin`].
+#[no_mangle]
+pub unsafe extern "C" fn coinselect_select_coin_bnb(
+    inputs: *const CoinselectOutputGroup,
+    inputs_len: usize,
+    options: *const CoinselectOptions,
+    out: *mut CoinselectSelection,
+) -> CoinselectStatus {
+    select_c(inputs, inputs_len, options, out, select_coin_bnb)
+}
+
+/// Selects with CoinGrinder, see [`coinselect_select_coin`] and [`select_coin_coingrinder`].
+///
+/// # Safety
+///
+/// See [`coinselect_select_coin`].
+#[no_mangle]
+pub unsafe extern "C" fn coinselect_select_coin_coingrinder(
+    inputs: *const CoinselectOutputGroup,
+    inputs_len: usize,
+    options: *const CoinselectOptions,
+    out: *mut CoinselectSelection,
+) -> CoinselectStatus {
+    select_c(inputs, inputs_len, options, out, select_coin_coingrinder)
+}
+
+/// Selects the oldest inputs first, see [`coinselect_select_coin`] and [`select_coin_fifo`].
+///
+/// # Safety
+///
+/// See [`coinselect_select_coin`].
+#[no_mangle]
+pub unsafe extern "C" fn coinselect_select_coin_fifo(
+    inputs: *const CoinselectOutputGroup,
+    inputs_len: usize,
+    options: *const CoinselectOptions,
+    out: *mut CoinselectSelection,
+) -> CoinselectStatus {
+    select_c(inputs, inputs_len, options, out, select_coin_fifo)
+}
+
+/// Selects with the Knapsack algorithm, see [`coinselect_select_coin`] and [`select_coin_knapsack`].
+///
+/// # Safety
+///
+/// See [`coinselect_select_coin`].
+#[no_mangle]
+pub unsafe extern "C" fn coinselect_select_coin_knapsack(
+    inputs: *const CoinselectOutputGroup,
+    inputs_len: usize,
+    options: *const CoinselectOptions,
+    out: *mut CoinselectSelection,
+) -> CoinselectStatus {
+    select_c(inputs, inputs_len, options, out, select_coin_knapsack)
+}
+
+/// Selects the lowest larger input, or else the largest ones, see [`coinselect_select_coin`] and [`select_coin_lowestlarger`].
+///
+/// # Safety
+///
+/// See [`coinselect_select_coin`].
+#[no_mangle]
+pub unsafe extern "C" fn coinselect_select_coin_lowestlarger(
+    inputs: *const CoinselectOutputGroup,
+    inputs_len: usize,
+    options: *const CoinselectOptions,
+    out: *mut CoinselectSelection,
+) -> CoinselectStatus {
+    select_c(inputs, inputs_len, options, out, select_coin_lowestlarger)
+}
+
+/// Selects with a single random draw, see [`coinselect_select_coin`] and [`select_coin_srd`].
+///
+/// # Safety
+///
+/// See [`coinselect_select_coin`].
+#[no_mangle]
+pub unsafe extern "C" fn coinselect_select_coin_srd(
+    inputs: *const CoinselectOutputGroup,
+    inputs_len: usize,
+    options: *const CoinselectOptions,
+    out: *mut CoinselectSelection,
+) -> CoinselectStatus {
+    select_c(inputs, inputs_len, options, out, select_coin_srd)
+}
+
+/// Releases the buffer of a selection written by one of the selection functions, and resets it.
+///
+/// # Safety
+///
+/// `selection` must be null or point to a selection written by this library, not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn coinselect_selection_free(selection: *mut CoinselectSelection) {
+    if selection.is_null() {
+        return;
+    }
+    // Nothing can be reported to the caller, but a panic must not unwind into C
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        let selection = &mut *selection;
+        if !selection.selected_inputs.is_null() {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                selection.selected_inputs,
+                selection.selected_inputs_len,
+            )));
+        }
+        selection.selected_inputs = ptr::null_mut();
+        selection.selected_inputs_len = 0;
+    }));
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::capi::{
+        coinselect_select_coin_fifo, coinselect_selection_free, select_c, CoinselectExcessStrategy,
+        CoinselectOptions, CoinselectOutputGroup, CoinselectSelection, CoinselectStatus,
+    };
+    use std::{mem::MaybeUninit, ptr, slice};
+
+    fn setup_inputs() -> Vec<CoinselectOutputGroup> {
+        [100_000, 200_000, 300_000]
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| CoinselectOutputGroup {
+                value,
+                weight: 272,
+                input_count: 1,
+                creation_sequence: 2 - index as u32,
+                has_creation_sequence: true,
+                ancestor_weight: 0,
+                ancestor_fee: 0,
+                has_ancestors: false,
+            })
+            .collect()
+    }
+
+    fn setup_options(target_value: u64) -> CoinselectOptions {
+        CoinselectOptions {
+            target_value,
+            target_feerate: 0.5,
+            long_term_feerate: 0.4,
+            has_long_term_feerate: true,
+            min_absolute_fee: 0,
+            base_weight: 40,
+            change_weight: 124,
+            change_cost: 100,
+            avg_input_weight: 272,
+            avg_output_weight: 124,
+            min_change_value: 500,
+            excess_strategy: CoinselectExcessStrategy::ToChange as u32,
+            must_spend: ptr::null(),
+            must_spend_len: 0,
+            excluded: ptr::null(),
+            excluded_len: 0,
+        }
+    }
+
+    #[test]
+    fn test_capi_select() {
+        let inputs = setup_inputs();
+        let excluded = [2usize];
+        let options = CoinselectOptions {
+            excluded: excluded.as_ptr(),
+            excluded_len: excluded.len(),
+            ..setup_options(150_000)
+        };
+        let mut out = MaybeUninit::<CoinselectSelection>::uninit();
+        let status = unsafe {
+            coinselect_select_coin_fifo(inputs.as_ptr(), inputs.len(), &options, out.as_mut_ptr())
+        };
+        assert_eq!(status, CoinselectStatus::Ok);
+        let mut selection = unsafe { out.assume_init() };
+        let selected = unsafe {
+            slice::from_raw_parts(selection.selected_inputs, selection.selected_inputs_len)
+        };
+        // The oldest input is excluded
+        assert_eq!(selected, &[1]);
+        assert!(selection.change_value > 0);
+        unsafe { coinselect_selection_free(&mut selection) };
+        assert!(selection.selected_inputs.is_null());
+        assert_eq!(selection.selected_inputs_len, 0);
+    }
+
+    #[test]
+    fn test_capi_errors() {
+        let inputs = setup_inputs();
+        let mut out = MaybeUninit::<CoinselectSelection>::uninit();
+        let status = unsafe {
+            coinselect_select_coin_fifo(
+                inputs.as_ptr(),
+                inputs.len(),
+                &setup_options(1_000_000),
+                out.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, CoinselectStatus::InsufficientFunds);
+        assert_eq!(status as i32, 3);
+
+        let options = CoinselectOptions {
+            must_spend_len: 1,
+            ..setup_options(150_000)
+        };
+        let status = unsafe {
+            coinselect_select_coin_fifo(inputs.as_ptr(), inputs.len(), &options, out.as_mut_ptr())
+        };
+        assert_eq!(status, CoinselectStatus::InvalidArgument);
+        let options = CoinselectOptions {
+            excess_strategy: 3,
+            ..setup_options(150_000)
+        };
+        let status = unsafe {
+            coinselect_select_coin_fifo(inputs.as_ptr(), inputs.len(), &options, out.as_mut_ptr())
+        };
+        assert_eq!(status, CoinselectStatus::InvalidArgument);
+        let status = unsafe {
+            coinselect_select_coin_fifo(
+                inputs.as_ptr(),
+                inputs.len(),
+                &setup_options(150_000),
+                ptr::null_mut(),
+            )
+        };
+        assert_eq!(status, CoinselectStatus::InvalidArgument);
+    }
+
+    #[test]
+    fn test_capi_panic() {
+        let inputs = setup_inputs();
+        let mut out = MaybeUninit::<CoinselectSelection>::uninit();
+        let status = unsafe {
+            select_c(
+                inputs.as_ptr(),
+                inputs.len(),
+                &setup_options(150_000),
+                out.as_mut_ptr(),
+                |_, _| panic!("selection bug"),
+            )
+        };
+        assert_eq!(status, CoinselectStatus::Panic);
+        assert_eq!(status as i32, -2);
+    }
+}
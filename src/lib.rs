@@ -1,10 +1,49 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
-/// Collection of coin selection algorithms including Knapsack, Branch and Bound (BNB), First-In First-Out (FIFO), Single-Random-Draw (SRD), and Lowest Larger
+extern crate alloc;
+// The tests print with the macros of std, which the test harness links anyway
+#[cfg(test)]
+extern crate std;
+
+/// Collection of coin selection algorithms including Knapsack, Branch and Bound (BNB), First-In First-Out (FIFO), Single-Random-Draw (SRD), Lowest Larger, and CoinGrinder
 pub mod algorithms;
+/// Helpers to build selection options from several recipients and to batch pending payouts
+pub mod batch;
+/// Adapter running the coin selection of this library as a BDK coin selection algorithm
+#[cfg(feature = "bdk")]
+pub mod bdk;
+/// Conversions from rust-bitcoin types into output groups and selection options
+#[cfg(feature = "bitcoin")]
+pub mod bitcoin;
+/// C interface of the selection functions, declared in the cbindgen generated `include/rust_coinselect.h`
+#[cfg(feature = "capi")]
+pub mod capi;
+/// Import of the candidates listed by the `listunspent` RPC of Bitcoin Core
+#[cfg(feature = "listunspent")]
+pub mod listunspent;
+/// Builder of the unsigned transaction, as a PSBT, spending the inputs of a selection
+#[cfg(feature = "bitcoin")]
+pub mod psbt;
+/// Python bindings of the types and selection functions, with an exception per selection error
+#[cfg(feature = "python")]
+pub mod python;
+/// Selection of the inputs of a replacement transaction following the BIP125 rules
+pub mod rbf;
+/// Thread-safe reservation of selected inputs, so concurrent sessions never select the same coins
+#[cfg(feature = "std")]
+pub mod reservation;
 /// Wrapper API that runs all coin selection algorithms in parallel and returns the result with lowest waste
 pub mod selectcoin;
+/// Wallet-lifetime simulation replaying payments and feerates against a pool of output groups
+pub mod simulation;
 /// Core types and structs used throughout the library including OutputGroup and CoinSelectionOpt
 pub mod types;
 /// Helper functions with tests for fee calculation, weight computation, and waste metrics
 pub mod utils;
+/// JavaScript bindings of the selection functions for WebAssembly
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+
+/// Worst-case weight estimates of Bitcoin inputs and outputs for every standard script type
+pub mod weight;
@@ -0,0 +1,318 @@
+use crate::{
+    types::{Ancestors, OutputGroup},
+    weight,
+};
+use serde::Deserialize;
+use std::fmt;
+
+/// An entry of the `listunspent` RPC of Bitcoin Core.
+///
+/// Only the fields used to build an [`OutputGroup`] are kept.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UnspentOutput {
+    /// Id of the transaction creating the output.
+    pub txid: String,
+    /// Index of the output in its transaction.
+    pub vout: u32,
+    /// Value of the output in BTC.
+    pub amount: f64,
+    /// Number of confirmations, 0 for unconfirmed outputs.
+    pub confirmations: u32,
+    /// Output descriptor spending the output, only reported for solvable outputs.
+    #[serde(default)]
+    pub desc: Option<String>,
+    /// Hex encoded script pubkey of the output.
+    #[serde(rename = "scriptPubKey")]
+    pub script_pubkey: String,
+    /// Whether the wallet knows how to spend the output.
+    pub solvable: bool,
+    /// Whether the output is considered safe to spend.
+    pub safe: bool,
+    /// Virtual size of the unconfirmed ancestors of the output, its own transaction included, only reported for
+    /// unconfirmed outputs.
+    #[serde(default, rename = "ancestorsize")]
+    pub ancestor_size: Option<u64>,
+    /// Fees in sats of the unconfirmed ancestors of the output, its own transaction included, only reported for
+    /// unconfirmed outputs.
+    #[serde(default, rename = "ancestorfees")]
+    pub ancestor_fees: Option<u64>,
+}
+
+impl UnspentOutput {
+    /// Returns the value of the output in sats.
+    pub fn value(&self) -> u64 {
+        (self.amount * 100_000_000.0).round() as u64
+    }
+
+    /// Returns the weight of an input spending the output, from its descriptor or, without one, from its script pubkey.
+    ///
+    /// Taproot outputs are assumed to be spent through the key path. Pay to script hash outputs need a descriptor, since
+    /// their script pubkey doesn't tell the nested script. Returns `None` if the script type isn't supported.
+    pub fn input_weight(&self) -> Option<u64> {
+        match self.desc.as_deref() {
+            Some(descriptor) => descriptor_input_weight(descriptor),
+            None => script_pubkey_input_weight(&self.script_pubkey),
+        }
+    }
+
+    /// Returns the unconfirmed ancestors of the output, its own transaction included, or `None` once confirmed.
+    pub fn ancestors(&self) -> Option<Ancestors> {
+        match (self.ancestor_size, self.ancestor_fees) {
+            (Some(size), Some(fees)) => Some(Ancestors {
+                weight: size * 4,
+                fee: fees,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Errors raised while importing the output of `listunspent`.
+#[derive(Debug)]
+pub enum ListUnspentError {
+    /// The JSON isn't a valid `listunspent` output.
+    Json(serde_json::Error),
+    /// The weight of the input spending the output `txid:vout` can't be derived from its descriptor or script pubkey.
+    UnsupportedScript {
+        /// Id of the transaction creating the output.
+        txid: String,
+        /// Index of the output in its transaction.
+        vout: u32,
+    },
+}
+
+impl fmt::Display for ListUnspentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListUnspentError::Json(error) => write!(f, "Invalid listunspent JSON: {}", error),
+            ListUnspentError::UnsupportedScript { txid, vout } => {
+                write!(f, "Unsupported script type for output {}:{}", txid, vout)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ListUnspentError {}
+
+/// Parses the JSON output of `listunspent`, keeping only the outputs that are safe to spend and solvable.
+pub fn parse_listunspent(json: &str) -> Result<Vec<UnspentOutput>, ListUnspentError> {
+    let unspent: Vec<UnspentOutput> = serde_json::from_str(json).map_err(ListUnspentError::Json)?;
+    Ok(unspent
+        .into_iter()
+        .filter(|output| output.safe && output.solvable)
+        .collect())
+}
+
+/// Creates one [`OutputGroup`] per unspent output, in the same order.
+///
+/// The creation sequence orders the outputs from the most confirmed to the unconfirmed ones, so FIFO spends the oldest first.
+/// The ancestors of unconfirmed outputs are kept, so that the selection bumps them to the target feerate if needed.
+pub fn output_groups(unspent: &[UnspentOutput]) -> Result<Vec<OutputGroup>, ListUnspentError> {
+    let max_confirmations = unspent
+        .iter()
+        .map(|output| output.confirmations)
+        .max()
+        .unwrap_or(0);
+    unspent
+        .iter()
+        .map(|output| {
+            let weight =
+                output
+                    .input_weight()
+                    .ok_or_else(|| ListUnspentError::UnsupportedScript {
+                        txid: output.txid.clone(),
+                        vout: output.vout,
+                    })?;
+            Ok(OutputGroup {
+                value: output.value(),
+                weight,
+                input_count: 1,
+                creation_sequence: Some(max_confirmations - output.confirmations),
+                ancestors: output.ancestors(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the input weight of the standard single key and multisig descriptors.
+fn descriptor_input_weight(descriptor: &str) -> Option<u64> {
+    // The checksum isn't needed to identify the script type
+    let descriptor = descriptor.split('#').next().unwrap_or(descriptor);
+    if descriptor.starts_with("pkh(") {
+        Some(weight::p2pkh_input_weight())
+    } else if descriptor.starts_with("sh(wpkh(") {
+        Some(weight::p2sh_p2wpkh_input_weight())
+    } else if descriptor.starts_with("wpkh(") {
+        Some(weight::p2wpkh_input_weight())
+    } else if descriptor.starts_with("tr(") && !descriptor.contains(',') {
+        // Descriptors with script paths may be spent through a heavier script path
+        Some(weight::p2tr_key_path_input_weight())
+    } else if let Some(multisig) = descriptor
+        .strip_prefix("wsh(multi(")
+        .or_else(|| descriptor.strip_prefix("wsh(sortedmulti("))
+    {
+        // The threshold is followed by the keys, which contain no comma
+        let mut arguments = multisig.trim_end_matches(')').split(',');
+        let m: u64 = arguments.next()?.parse().ok()?;
+        let n = arguments.count() as u64;
+        Some(weight::p2wsh_multisig_input_weight(m, n))
+    } else {
+        None
+    }
+}
+
+/// Returns the input weight of the standard single key script pubkeys, given in hex.
+fn script_pubkey_input_weight(script_pubkey: &str) -> Option<u64> {
+    let script_pubkey = script_pubkey.to_ascii_lowercase();
+    match script_pubkey.len() / 2 {
+        25 if script_pubkey.starts_with("76a914") && script_pubkey.ends_with("88ac") => {
+            Some(weight::p2pkh_input_weight())
+        }
+        22 if script_pubkey.starts_with("0014") => Some(weight::p2wpkh_input_weight()),
+        34 if script_pubkey.starts_with("5120") => Some(weight::p2tr_key_path_input_weight()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        algorithms::fifo::select_coin_fifo,
+        listunspent::{output_groups, parse_listunspent, ListUnspentError},
+        types::{Ancestors, CoinSelectionOpt, ExcessStrategy},
+        weight,
+    };
+
+    const LISTUNSPENT: &str = r#"[
+        {
+            "txid": "e9269b40306f10e2636414e514366474d5736256844882ffefd794f688b648b0",
+            "vout": 0,
+            "address": "bc1q9ll6ngymklaheh4yfq6dwlhgr3yutuxvp9rxgq",
+            "label": "",
+            "scriptPubKey": "00142fffa9a09bb7fa7dced44834d77ee81c49c5f0cc",
+            "amount": 0.00150000,
+            "confirmations": 120,
+            "spendable": true,
+            "solvable": true,
+            "desc": "wpkh([d34db33f/84h/0h/0h/0/1]032d49f270684da5a422df37bf9818aa178ad89a975643c4928444aed78a7dd341)#q6vpdzue",
+            "parent_descs": [],
+            "safe": true
+        },
+        {
+            "txid": "e1926a751f36c25541263ca4b621f1e3376e15117c170e60ba82b113bda3cba6",
+            "vout": 1,
+            "scriptPubKey": "0020e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "amount": 1.5,
+            "confirmations": 3000,
+            "spendable": true,
+            "solvable": true,
+            "desc": "wsh(sortedmulti(2,[d34db33f/48h/0h/0h/2h]02e96fe52ef0e22d2f131dd425ce1893073a3c6ad20e8cac36726393dfb4856a4c,03739ea9368ff2b1fdc4db2f160191e980190367501cc2b0c93a566fababd01064,02f2a0bdce72551e68dfbebf81d770924836cbb256a63e9987ea869eabac4859c1))#8x9mjwny",
+            "safe": true
+        },
+        {
+            "txid": "0c20171f1558eb6c032a5916e8ce0b446fef6f7b102b2aabcfc83a8367b0f53b",
+            "vout": 2,
+            "scriptPubKey": "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+            "amount": 0.0005,
+            "confirmations": 0,
+            "ancestorcount": 1,
+            "ancestorsize": 153,
+            "ancestorfees": 153,
+            "spendable": true,
+            "solvable": true,
+            "safe": true
+        },
+        {
+            "txid": "f2ccf54fa95a13b092d4f90c9cb215d11c858fd2964a45f3f997b3d4815e24e4",
+            "vout": 1,
+            "scriptPubKey": "00142fffa9a09bb7fa7dced44834d77ee81c49c5f0cc",
+            "amount": 0.2,
+            "confirmations": 0,
+            "spendable": true,
+            "solvable": true,
+            "safe": false
+        }
+    ]"#;
+
+    #[test]
+    fn test_import_listunspent() {
+        // The unsafe output is left out
+        let unspent = parse_listunspent(LISTUNSPENT).unwrap();
+        assert_eq!(unspent.len(), 3);
+        assert_eq!(unspent[0].value(), 150_000);
+        assert_eq!(unspent[1].value(), 150_000_000);
+
+        let groups = output_groups(&unspent).unwrap();
+        assert_eq!(groups[0].weight, weight::p2wpkh_input_weight());
+        assert_eq!(groups[1].weight, weight::p2wsh_multisig_input_weight(2, 3));
+        // Without descriptor, the weight is derived from the script pubkey
+        assert_eq!(groups[2].weight, weight::p2tr_key_path_input_weight());
+        assert_eq!(groups[0].creation_sequence, Some(2880));
+        assert_eq!(groups[1].creation_sequence, Some(0));
+        assert_eq!(groups[2].creation_sequence, Some(3000));
+        assert_eq!(groups[0].ancestors, None);
+        assert_eq!(
+            groups[2].ancestors,
+            Some(Ancestors {
+                weight: 612,
+                fee: 153
+            })
+        );
+
+        // FIFO spends the most confirmed output first
+        let options = CoinSelectionOpt {
+            target_value: 100_000,
+            target_feerate: 2.5,
+            long_term_feerate: Some(2.5),
+            min_absolute_fee: 0,
+            base_weight: 43 + 124,
+            change_weight: 124,
+            change_cost: 1000,
+            avg_input_weight: 272,
+            avg_output_weight: 124,
+            min_change_value: 294,
+            excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
+        };
+        let selection_output = select_coin_fifo(&groups, &options).unwrap();
+        assert_eq!(selection_output.selected_inputs, vec![1]);
+    }
+
+    #[test]
+    fn test_import_listunspent_errors() {
+        assert!(matches!(
+            parse_listunspent("{}"),
+            Err(ListUnspentError::Json(_))
+        ));
+
+        let mut unspent = parse_listunspent(LISTUNSPENT).unwrap();
+        unspent[2].script_pubkey = "6a".to_string();
+        assert!(matches!(
+            output_groups(&unspent),
+            Err(ListUnspentError::UnsupportedScript { vout: 2, .. })
+        ));
+
+        // A descriptor that isn't recognised isn't priced from the script pubkey
+        for desc in [
+            "sh(wsh(multi(2,03739ea9368ff2b1fdc4db2f160191e980190367501cc2b0c93a566fababd01064,02f2a0bdce72551e68dfbebf81d770924836cbb256a63e9987ea869eabac4859c1)))",
+            "sh(multi(1,03739ea9368ff2b1fdc4db2f160191e980190367501cc2b0c93a566fababd01064))",
+            "tr(03739ea9368ff2b1fdc4db2f160191e980190367501cc2b0c93a566fababd01064,pk(02f2a0bdce72551e68dfbebf81d770924836cbb256a63e9987ea869eabac4859c1))",
+        ] {
+            let mut unspent = parse_listunspent(LISTUNSPENT).unwrap();
+            unspent[0].script_pubkey = "a914e3b0c44298fc1c149afbf4c8996fb92427ae41e487".to_string();
+            unspent[0].desc = Some(desc.to_string());
+            assert!(matches!(
+                output_groups(&unspent),
+                Err(ListUnspentError::UnsupportedScript { vout: 0, .. })
+            ));
+        }
+
+        // Nor is a pay to script hash output without descriptor
+        let mut unspent = parse_listunspent(LISTUNSPENT).unwrap();
+        unspent[0].script_pubkey = "a914e3b0c44298fc1c149afbf4c8996fb92427ae41e487".to_string();
+        unspent[0].desc = None;
+        assert!(output_groups(&unspent).is_err());
+    }
+}
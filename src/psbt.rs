@@ -0,0 +1,284 @@
+use crate::types::SelectionOutput;
+use ::bitcoin::{
+    absolute::LockTime, psbt, transaction::Version, Amount, OutPoint, Psbt, ScriptBuf, Sequence,
+    Transaction, TxIn, TxOut,
+};
+use std::fmt;
+
+/// Errors raised while building a [`Psbt`] from a [`SelectionOutput`].
+#[derive(Debug)]
+pub enum PsbtBuilderError {
+    /// A selected input index doesn't refer to a candidate.
+    InputOutOfRange(usize),
+    /// The selected candidates are worth less than the recipients and the fee of the selection.
+    InsufficientFunds,
+    /// The unsigned transaction couldn't be turned into a PSBT.
+    Psbt(psbt::Error),
+}
+
+impl fmt::Display for PsbtBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PsbtBuilderError::InputOutOfRange(index) => {
+                write!(f, "Selected input {} is not a candidate", index)
+            }
+            PsbtBuilderError::InsufficientFunds => {
+                write!(
+                    f,
+                    "The selected inputs can't pay the recipients and the fee"
+                )
+            }
+            PsbtBuilderError::Psbt(error) => write!(f, "Invalid PSBT: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PsbtBuilderError {}
+
+/// Builds the unsigned transaction paying a set of recipients from a [`SelectionOutput`], as a [`Psbt`].
+///
+/// The transaction spends the selected candidates and pays the recipients, the fee and change of the selection.
+/// Change below the dust limit of the change script is added to the fee instead, and the excess the selection leaves to
+/// the recipients (see [`crate::types::ExcessStrategy::ToRecipient`]) is added to the first one.
+#[derive(Debug, Clone)]
+pub struct PsbtBuilder {
+    recipients: Vec<TxOut>,
+    change_script: ScriptBuf,
+    version: Version,
+    lock_time: LockTime,
+    sequence: Sequence,
+}
+
+impl PsbtBuilder {
+    /// Creates a builder paying `recipients`, with change sent to `change_script`.
+    ///
+    /// The transaction is version 2 without lock time, and its inputs signal replaceability.
+    pub fn new(recipients: Vec<TxOut>, change_script: ScriptBuf) -> Self {
+        PsbtBuilder {
+            recipients,
+            change_script,
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+        }
+    }
+
+    /// Sets the version of the transaction.
+    pub fn version(mut self, version: Version) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the lock time of the transaction.
+    pub fn lock_time(mut self, lock_time: LockTime) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Sets the sequence of every input.
+    pub fn sequence(mut self, sequence: Sequence) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Builds the PSBT spending the inputs of `selection_output`, whose indices refer to `candidates`.
+    ///
+    /// The selection must have been made for the recipients of the builder. The spent outputs of segwit and P2SH inputs are
+    /// set as witness UTXOs, legacy inputs need their previous transaction to be added before signing.
+    pub fn build(
+        &self,
+        candidates: &[(OutPoint, TxOut)],
+        selection_output: &SelectionOutput,
+    ) -> Result<Psbt, PsbtBuilderError> {
+        let mut spent_outputs = Vec::with_capacity(selection_output.selected_inputs.len());
+        for &index in &selection_output.selected_inputs {
+            match candidates.get(index) {
+                Some(candidate) => spent_outputs.push(candidate),
+                None => return Err(PsbtBuilderError::InputOutOfRange(index)),
+            }
+        }
+
+        let input_value: u64 = spent_outputs
+            .iter()
+            .map(|(_, txout)| txout.value.to_sat())
+            .sum();
+        let recipients_value: u64 = self
+            .recipients
+            .iter()
+            .map(|txout| txout.value.to_sat())
+            .sum();
+        // Dust change isn't worth creating, it goes to the fee
+        let change_value = selection_output
+            .change_value
+            .filter(|&change| change >= self.change_script.minimal_non_dust().to_sat());
+        let excess = input_value
+            .checked_sub(recipients_value + selection_output.fee)
+            .ok_or(PsbtBuilderError::InsufficientFunds)?;
+        // Whatever neither goes to the change nor to the fee was left to the recipients by the selection
+        let excess_to_recipient = match selection_output.change_value {
+            Some(_) => 0,
+            None => excess,
+        };
+
+        let mut outputs = self.recipients.clone();
+        if let Some(first) = outputs.first_mut() {
+            first.value += Amount::from_sat(excess_to_recipient);
+        }
+        if let Some(change) = change_value {
+            outputs.push(TxOut {
+                value: Amount::from_sat(change),
+                script_pubkey: self.change_script.clone(),
+            });
+        }
+
+        let transaction = Transaction {
+            version: self.version,
+            lock_time: self.lock_time,
+            input: spent_outputs
+                .iter()
+                .map(|(outpoint, _)| TxIn {
+                    previous_output: *outpoint,
+                    sequence: self.sequence,
+                    ..TxIn::default()
+                })
+                .collect(),
+            output: outputs,
+        };
+        let mut psbt = Psbt::from_unsigned_tx(transaction).map_err(PsbtBuilderError::Psbt)?;
+        for (input, (_, txout)) in psbt.inputs.iter_mut().zip(spent_outputs) {
+            if txout.script_pubkey.is_witness_program() || txout.script_pubkey.is_p2sh() {
+                input.witness_utxo = Some(txout.clone());
+            }
+        }
+        Ok(psbt)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        bitcoin::output_groups,
+        psbt::{PsbtBuilder, PsbtBuilderError},
+        selectcoin::select_coin,
+        types::{CoinSelectionOpt, SelectionOutput, WasteMetric},
+    };
+    use ::bitcoin::{hashes::Hash, Amount, FeeRate, OutPoint, ScriptBuf, TxOut, Txid, WPubkeyHash};
+
+    fn setup_candidates() -> Vec<(OutPoint, TxOut)> {
+        [100_000, 250_000, 50_000]
+            .into_iter()
+            .enumerate()
+            .map(|(vout, value)| {
+                (
+                    OutPoint::new(Txid::all_zeros(), vout as u32),
+                    TxOut {
+                        value: Amount::from_sat(value),
+                        script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn setup_recipient(value: u64) -> TxOut {
+        TxOut {
+            value: Amount::from_sat(value),
+            script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()),
+        }
+    }
+
+    fn setup_selection_output(
+        selected_inputs: Vec<usize>,
+        fee: u64,
+        change_value: Option<u64>,
+    ) -> SelectionOutput {
+        SelectionOutput {
+            selected_inputs,
+            waste: WasteMetric(0),
+            total_value: 0,
+            total_weight: 0,
+            fee,
+            change_value,
+            excess: 0,
+            feerate: 0.0,
+            algorithm: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_build_psbt() {
+        let candidates = setup_candidates();
+        let recipient = setup_recipient(120_000);
+        let change_script = ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros());
+        let options = CoinSelectionOpt::from_bitcoin(
+            std::slice::from_ref(&recipient),
+            &change_script,
+            FeeRate::from_sat_per_vb(10).unwrap(),
+            FeeRate::from_sat_per_vb(5).unwrap(),
+        )
+        .unwrap();
+        let groups = output_groups(&candidates).unwrap();
+        let selection_output = select_coin(&groups, &options).unwrap();
+
+        let psbt = PsbtBuilder::new(vec![recipient], change_script.clone())
+            .build(&candidates, &selection_output)
+            .unwrap();
+        let tx = &psbt.unsigned_tx;
+        assert_eq!(tx.input.len(), selection_output.selected_inputs.len());
+        assert!(psbt.inputs.iter().all(|input| input.witness_utxo.is_some()));
+        assert_eq!(tx.output[0].value, Amount::from_sat(120_000));
+        if let Some(change_value) = selection_output.change_value {
+            assert_eq!(tx.output[1].value, Amount::from_sat(change_value));
+            assert_eq!(tx.output[1].script_pubkey, change_script);
+        }
+
+        // The fee paid is the one of the selection, which meets the requested feerate
+        let input_value: u64 = psbt
+            .inputs
+            .iter()
+            .map(|input| input.witness_utxo.as_ref().unwrap().value.to_sat())
+            .sum();
+        let output_value: u64 = tx.output.iter().map(|txout| txout.value.to_sat()).sum();
+        assert_eq!(input_value - output_value, selection_output.fee);
+        let weight = options.base_weight
+            + selection_output.total_weight
+            + selection_output
+                .change_value
+                .map_or(0, |_| options.change_weight);
+        assert!(selection_output.fee as f32 >= weight as f32 * options.target_feerate);
+    }
+
+    #[test]
+    fn test_build_psbt_change_and_excess() {
+        let candidates = setup_candidates();
+        let change_script = ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros());
+        let builder = PsbtBuilder::new(vec![setup_recipient(99_000)], change_script);
+
+        // Change below the 294 sats dust limit goes to the fee
+        let selection_output = setup_selection_output(vec![0], 800, Some(200));
+        let psbt = builder.build(&candidates, &selection_output).unwrap();
+        assert_eq!(psbt.unsigned_tx.output.len(), 1);
+        assert_eq!(psbt.unsigned_tx.output[0].value, Amount::from_sat(99_000));
+
+        // Without change, the excess left to the recipient is added to its output
+        let selection_output = SelectionOutput {
+            excess: 200,
+            ..setup_selection_output(vec![0], 800, None)
+        };
+        let psbt = builder.build(&candidates, &selection_output).unwrap();
+        assert_eq!(psbt.unsigned_tx.output.len(), 1);
+        assert_eq!(psbt.unsigned_tx.output[0].value, Amount::from_sat(99_200));
+
+        let selection_output = setup_selection_output(vec![3], 800, None);
+        assert!(matches!(
+            builder.build(&candidates, &selection_output),
+            Err(PsbtBuilderError::InputOutOfRange(3))
+        ));
+        let selection_output = setup_selection_output(vec![2], 800, None);
+        assert!(matches!(
+            builder.build(&candidates, &selection_output),
+            Err(PsbtBuilderError::InsufficientFunds)
+        ));
+    }
+}
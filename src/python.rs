@@ -0,0 +1,567 @@
+// The #[pyfunction] expansion of pyo3 0.22 converts the returned PyErr into itself
+#![allow(clippy::useless_conversion)]
+
+use crate::{
+    algorithms::{
+        bnb::select_coin_bnb, coingrinder::select_coin_coingrinder, fifo::select_coin_fifo,
+        knapsack::select_coin_knapsack, lowestlarger::select_coin_lowestlarger,
+        srd::select_coin_srd,
+    },
+    selectcoin::{select_coin, select_coin_with_seed},
+    types::{
+        Ancestors, CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError, SelectionOutput,
+    },
+};
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+
+create_exception!(
+    rust_coinselect,
+    PySelectionError,
+    PyException,
+    "Base class of the errors raised by the selection functions."
+);
+create_exception!(
+    rust_coinselect,
+    NonPositiveFeeRateError,
+    PySelectionError,
+    "The target feerate isn't positive."
+);
+create_exception!(
+    rust_coinselect,
+    AbnormallyHighFeeRateError,
+    PySelectionError,
+    "The target feerate is abnormally high."
+);
+create_exception!(
+    rust_coinselect,
+    InsufficientFundsError,
+    PySelectionError,
+    "The inputs can't fund the target and the fee."
+);
+create_exception!(
+    rust_coinselect,
+    NoSolutionFoundError,
+    PySelectionError,
+    "The algorithm found no selection."
+);
+create_exception!(
+    rust_coinselect,
+    InsufficientReplacementFeeError,
+    PySelectionError,
+    "The replacement can't pay the fee required by BIP125."
+);
+create_exception!(
+    rust_coinselect,
+    MustSpendOutOfRangeError,
+    PySelectionError,
+    "A must-spend index is out of range of the inputs."
+);
+create_exception!(
+    rust_coinselect,
+    InputReservedError,
+    PySelectionError,
+    "An input is reserved by another transaction."
+);
+
+impl From<SelectionError> for PyErr {
+    fn from(error: SelectionError) -> Self {
+        let message = error.to_string();
+        match error {
+            SelectionError::NonPositiveFeeRate => NonPositiveFeeRateError::new_err(message),
+            SelectionError::AbnormallyHighFeeRate => AbnormallyHighFeeRateError::new_err(message),
+            SelectionError::InsufficientFunds => InsufficientFundsError::new_err(message),
+            SelectionError::NoSolutionFound => NoSolutionFoundError::new_err(message),
+            SelectionError::InsufficientReplacementFee => {
+                InsufficientReplacementFeeError::new_err(message)
+            }
+            SelectionError::MustSpendOutOfRange => MustSpendOutOfRangeError::new_err(message),
+            SelectionError::InputReserved => InputReservedError::new_err(message),
+        }
+    }
+}
+
+/// See [`ExcessStrategy`].
+#[pyclass(name = "ExcessStrategy", eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PyExcessStrategy {
+    /// See [`ExcessStrategy::ToFee`].
+    ToFee,
+    /// See [`ExcessStrategy::ToRecipient`].
+    ToRecipient,
+    /// See [`ExcessStrategy::ToChange`].
+    ToChange,
+}
+
+impl From<PyExcessStrategy> for ExcessStrategy {
+    fn from(strategy: PyExcessStrategy) -> Self {
+        match strategy {
+            PyExcessStrategy::ToFee => ExcessStrategy::ToFee,
+            PyExcessStrategy::ToRecipient => ExcessStrategy::ToRecipient,
+            PyExcessStrategy::ToChange => ExcessStrategy::ToChange,
+        }
+    }
+}
+
+/// See [`OutputGroup`].
+#[pyclass(name = "OutputGroup", get_all, set_all)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PyOutputGroup {
+    /// Total value of the UTXO(s).
+    pub value: u64,
+    /// Total weight of spending the UTXO(s).
+    pub weight: u64,
+    /// The total number of inputs.
+    pub input_count: usize,
+    /// Relative creation sequence, used only for FIFO selection.
+    pub creation_sequence: Option<u32>,
+    /// The unconfirmed ancestors, `None` once confirmed.
+    pub ancestors: Option<PyAncestors>,
+}
+
+#[pymethods]
+impl PyOutputGroup {
+    #[new]
+    #[pyo3(signature = (value, weight, input_count=1, creation_sequence=None, ancestors=None))]
+    fn new(
+        value: u64,
+        weight: u64,
+        input_count: usize,
+        creation_sequence: Option<u32>,
+        ancestors: Option<PyAncestors>,
+    ) -> Self {
+        PyOutputGroup {
+            value,
+            weight,
+            input_count,
+            creation_sequence,
+            ancestors,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", OutputGroup::from(self.clone()))
+    }
+}
+
+impl From<PyOutputGroup> for OutputGroup {
+    fn from(group: PyOutputGroup) -> Self {
+        OutputGroup {
+            value: group.value,
+            weight: group.weight,
+            input_count: group.input_count,
+            creation_sequence: group.creation_sequence,
+            ancestors: group.ancestors.map(Ancestors::from),
+        }
+    }
+}
+
+/// See [`Ancestors`].
+#[pyclass(name = "Ancestors", get_all, set_all)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PyAncestors {
+    /// Total weight of the ancestors.
+    pub weight: u64,
+    /// Total fee paid by the ancestors.
+    pub fee: u64,
+}
+
+#[pymethods]
+impl PyAncestors {
+    #[new]
+    fn new(weight: u64, fee: u64) -> Self {
+        PyAncestors { weight, fee }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", Ancestors::from(*self))
+    }
+}
+
+impl From<PyAncestors> for Ancestors {
+    fn from(ancestors: PyAncestors) -> Self {
+        Ancestors {
+            weight: ancestors.weight,
+            fee: ancestors.fee,
+        }
+    }
+}
+
+/// See [`CoinSelectionOpt`].
+#[pyclass(name = "CoinSelectionOpt", get_all, set_all)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PyCoinSelectionOpt {
+    /// The value to select.
+    pub target_value: u64,
+    /// The target feerate in sats per weight unit.
+    pub target_feerate: f32,
+    /// The long term feerate in sats per weight unit.
+    pub long_term_feerate: Option<f32>,
+    /// Lowest possible transaction fee.
+    pub min_absolute_fee: u64,
+    /// Weight of the transaction other than the selected inputs.
+    pub base_weight: u64,
+    /// Weight added by a change output.
+    pub change_weight: u64,
+    /// Cost of creating and later spending a change output.
+    pub change_cost: u64,
+    /// Estimate of the average weight of an input.
+    pub avg_input_weight: u64,
+    /// Estimate of the average weight of an output.
+    pub avg_output_weight: u64,
+    /// Smallest change value worth creating.
+    pub min_change_value: u64,
+    /// Where the excess of the selection goes.
+    pub excess_strategy: PyExcessStrategy,
+    /// Indices of the inputs that must be spent.
+    pub must_spend: Vec<usize>,
+    /// Indices of the inputs that must not be selected.
+    pub excluded: Vec<usize>,
+}
+
+#[pymethods]
+impl PyCoinSelectionOpt {
+    #[new]
+    #[pyo3(signature = (
+        target_value,
+        target_feerate,
+        min_absolute_fee,
+        base_weight,
+        change_weight,
+        change_cost,
+        avg_input_weight,
+        avg_output_weight,
+        min_change_value,
+        excess_strategy,
+        long_term_feerate=None,
+        must_spend=Vec::new(),
+        excluded=Vec::new(),
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        target_value: u64,
+        target_feerate: f32,
+        min_absolute_fee: u64,
+        base_weight: u64,
+        change_weight: u64,
+        change_cost: u64,
+        avg_input_weight: u64,
+        avg_output_weight: u64,
+        min_change_value: u64,
+        excess_strategy: PyExcessStrategy,
+        long_term_feerate: Option<f32>,
+        must_spend: Vec<usize>,
+        excluded: Vec<usize>,
+    ) -> Self {
+        PyCoinSelectionOpt {
+            target_value,
+            target_feerate,
+            long_term_feerate,
+            min_absolute_fee,
+            base_weight,
+            change_weight,
+            change_cost,
+            avg_input_weight,
+            avg_output_weight,
+            min_change_value,
+            excess_strategy,
+            must_spend,
+            excluded,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", CoinSelectionOpt::from(self.clone()))
+    }
+}
+
+impl From<PyCoinSelectionOpt> for CoinSelectionOpt {
+    fn from(options: PyCoinSelectionOpt) -> Self {
+        CoinSelectionOpt {
+            target_value: options.target_value,
+            target_feerate: options.target_feerate,
+            long_term_feerate: options.long_term_feerate,
+            min_absolute_fee: options.min_absolute_fee,
+            base_weight: options.base_weight,
+            change_weight: options.change_weight,
+            change_cost: options.change_cost,
+            avg_input_weight: options.avg_input_weight,
+            avg_output_weight: options.avg_output_weight,
+            min_change_value: options.min_change_value,
+            excess_strategy: options.excess_strategy.into(),
+            must_spend: options.must_spend,
+            excluded: options.excluded,
+        }
+    }
+}
+
+/// See [`SelectionOutput`].
+#[pyclass(name = "SelectionOutput", get_all, frozen)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PySelectionOutput {
+    /// Indices of the selected inputs.
+    pub selected_inputs: Vec<usize>,
+    /// Waste of the selection.
+    pub waste: i64,
+    /// Total value of the selected inputs.
+    pub total_value: u64,
+    /// Total weight of the selected inputs.
+    pub total_weight: u64,
+    /// Fee paid by the transaction.
+    pub fee: u64,
+    /// Value of the change output, if any.
+    pub change_value: Option<u64>,
+    /// Excess left to the fee or the recipient.
+    pub excess: u64,
+    /// Effective feerate of the transaction in sats per weight unit.
+    pub feerate: f32,
+    /// Name of the algorithm which made the selection.
+    pub algorithm: String,
+}
+
+#[pymethods]
+impl PySelectionOutput {
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl From<SelectionOutput> for PySelectionOutput {
+    fn from(output: SelectionOutput) -> Self {
+        PySelectionOutput {
+            selected_inputs: output.selected_inputs,
+            waste: output.waste.0,
+            total_value: output.total_value,
+            total_weight: output.total_weight,
+            fee: output.fee,
+            change_value: output.change_value,
+            excess: output.excess,
+            feerate: output.feerate,
+            algorithm: output.algorithm,
+        }
+    }
+}
+
+/// Converts the Python arguments and runs `algorithm` without holding the GIL.
+fn select_py<F>(
+    py: Python<'_>,
+    inputs: Vec<PyOutputGroup>,
+    options: PyCoinSelectionOpt,
+    algorithm: F,
+) -> PyResult<PySelectionOutput>
+where
+    F: FnOnce(&[OutputGroup], &CoinSelectionOpt) -> Result<SelectionOutput, SelectionError> + Send,
+{
+    let inputs: Vec<OutputGroup> = inputs.into_iter().map(OutputGroup::from).collect();
+    let options = CoinSelectionOpt::from(options);
+    let selection_output = py.allow_threads(|| algorithm(&inputs, &options))?;
+    Ok(selection_output.into())
+}
+
+/// Runs every algorithm and returns the selection with the least waste, see [`select_coin`].
+#[pyfunction(name = "select_coin")]
+pub fn select_coin_py(
+    py: Python<'_>,
+    inputs: Vec<PyOutputGroup>,
+    options: PyCoinSelectionOpt,
+) -> PyResult<PySelectionOutput> {
+    select_py(py, inputs, options, select_coin)
+}
+
+/// Same as `select_coin`, with every randomized algorithm seeded from `seed`, see [`select_coin_with_seed`].
+#[pyfunction(name = "select_coin_with_seed")]
+pub fn select_coin_with_seed_py(
+    py: Python<'_>,
+    inputs: Vec<PyOutputGroup>,
+    options: PyCoinSelectionOpt,
+    seed: u64,
+) -> PyResult<PySelectionOutput> {
+    select_py(py, inputs, options, |inputs, options| {
+        select_coin_with_seed(inputs, options, seed)
+    })
+}
+
+/// Selects with Branch and Bound, see [`select_coin_bnb`].
+#[pyfunction(name = "select_coin_bnb")]
+pub fn select_coin_bnb_py(
+    py: Python<'_>,
+    inputs: Vec<PyOutputGroup>,
+    options: PyCoinSelectionOpt,
+) -> PyResult<PySelectionOutput> {
+    select_py(py, inputs, options, select_coin_bnb)
+}
+
+/// Selects with CoinGrinder, see [`select_coin_coingrinder`].
+#[pyfunction(name = "select_coin_coingrinder")]
+pub fn select_coin_coingrinder_py(
+    py: Python<'_>,
+    inputs: Vec<PyOutputGroup>,
+    options: PyCoinSelectionOpt,
+) -> PyResult<PySelectionOutput> {
+    select_py(py, inputs, options, select_coin_coingrinder)
+}
+
+/// Selects the oldest inputs first, see [`select_coin_fifo`].
+#[pyfunction(name = "select_coin_fifo")]
+pub fn select_coin_fifo_py(
+    py: Python<'_>,
+    inputs: Vec<PyOutputGroup>,
+    options: PyCoinSelectionOpt,
+) -> PyResult<PySelectionOutput> {
+    select_py(py, inputs, options, select_coin_fifo)
+}
+
+/// Selects with the Knapsack algorithm, see [`select_coin_knapsack`].
+#[pyfunction(name = "select_coin_knapsack")]
+pub fn select_coin_knapsack_py(
+    py: Python<'_>,
+    inputs: Vec<PyOutputGroup>,
+    options: PyCoinSelectionOpt,
+) -> PyResult<PySelectionOutput> {
+    select_py(py, inputs, options, select_coin_knapsack)
+}
+
+/// Selects the lowest larger input, or else the largest ones, see [`select_coin_lowestlarger`].
+#[pyfunction(name = "select_coin_lowestlarger")]
+pub fn select_coin_lowestlarger_py(
+    py: Python<'_>,
+    inputs: Vec<PyOutputGroup>,
+    options: PyCoinSelectionOpt,
+) -> PyResult<PySelectionOutput> {
+    select_py(py, inputs, options, select_coin_lowestlarger)
+}
+
+/// Selects with a single random draw, see [`select_coin_srd`].
+#[pyfunction(name = "select_coin_srd")]
+pub fn select_coin_srd_py(
+    py: Python<'_>,
+    inputs: Vec<PyOutputGroup>,
+    options: PyCoinSelectionOpt,
+) -> PyResult<PySelectionOutput> {
+    select_py(py, inputs, options, select_coin_srd)
+}
+
+/// The `rust_coinselect` Python module.
+#[pymodule]
+#[pyo3(name = "rust_coinselect")]
+pub fn python_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add_class::<PyExcessStrategy>()?;
+    m.add_class::<PyOutputGroup>()?;
+    m.add_class::<PyAncestors>()?;
+    m.add_class::<PyCoinSelectionOpt>()?;
+    m.add_class::<PySelectionOutput>()?;
+    m.add("SelectionError", py.get_type_bound::<PySelectionError>())?;
+    m.add(
+        "NonPositiveFeeRateError",
+        py.get_type_bound::<NonPositiveFeeRateError>(),
+    )?;
+    m.add(
+        "AbnormallyHighFeeRateError",
+        py.get_type_bound::<AbnormallyHighFeeRateError>(),
+    )?;
+    m.add(
+        "InsufficientFundsError",
+        py.get_type_bound::<InsufficientFundsError>(),
+    )?;
+    m.add(
+        "NoSolutionFoundError",
+        py.get_type_bound::<NoSolutionFoundError>(),
+    )?;
+    m.add(
+        "InsufficientReplacementFeeError",
+        py.get_type_bound::<InsufficientReplacementFeeError>(),
+    )?;
+    m.add(
+        "MustSpendOutOfRangeError",
+        py.get_type_bound::<MustSpendOutOfRangeError>(),
+    )?;
+    m.add(
+        "InputReservedError",
+        py.get_type_bound::<InputReservedError>(),
+    )?;
+    m.add_function(wrap_pyfunction!(select_coin_py, m)?)?;
+    m.add_function(wrap_pyfunction!(select_coin_with_seed_py, m)?)?;
+    m.add_function(wrap_pyfunction!(select_coin_bnb_py, m)?)?;
+    m.add_function(wrap_pyfunction!(select_coin_coingrinder_py, m)?)?;
+    m.add_function(wrap_pyfunction!(select_coin_fifo_py, m)?)?;
+    m.add_function(wrap_pyfunction!(select_coin_knapsack_py, m)?)?;
+    m.add_function(wrap_pyfunction!(select_coin_lowestlarger_py, m)?)?;
+    m.add_function(wrap_pyfunction!(select_coin_srd_py, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::python::python_module;
+    use pyo3::{prelude::*, types::PyDict, wrap_pymodule};
+
+    /// Runs `code` with the module imported as `cs`.
+    fn run_python(code: &str) -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let globals = PyDict::new_bound(py);
+            globals.set_item("cs", wrap_pymodule!(python_module)(py))?;
+            py.run_bound(code, Some(&globals), None)
+        })
+    }
+
+    const SETUP: &str = r#"
+inputs = [
+    cs.OutputGroup(100_000, 272, creation_sequence=2),
+    cs.OutputGroup(200_000, 272, creation_sequence=1),
+    cs.OutputGroup(300_000, 272, creation_sequence=0),
+]
+def options(target_value, **kwargs):
+    return cs.CoinSelectionOpt(
+        target_value, 0.5, 0, 40, 124, 100, 272, 124, 500,
+        cs.ExcessStrategy.ToChange, long_term_feerate=0.4, **kwargs
+    )
+"#;
+
+    #[test]
+    fn test_python_select() {
+        run_python(&format!(
+            "{}{}",
+            SETUP,
+            r#"
+selection = cs.select_coin_fifo(inputs, options(150_000, excluded=[2]))
+assert selection.selected_inputs == [1], selection
+assert selection.change_value > 0
+assert selection.algorithm == "fifo"
+for select in [cs.select_coin, cs.select_coin_bnb, cs.select_coin_coingrinder, cs.select_coin_fifo,
+               cs.select_coin_knapsack, cs.select_coin_lowestlarger, cs.select_coin_srd]:
+    try:
+        assert select(inputs, options(150_000)).total_value >= 150_000
+    except cs.NoSolutionFoundError:
+        pass
+first = cs.select_coin_with_seed(inputs, options(150_000), 7)
+assert cs.select_coin_with_seed(inputs, options(150_000), 7).selected_inputs == first.selected_inputs
+"#
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn test_python_errors() {
+        run_python(&format!(
+            "{}{}",
+            SETUP,
+            r#"
+try:
+    cs.select_coin(inputs, options(1_000_000))
+    assert False
+except cs.InsufficientFundsError as error:
+    assert isinstance(error, cs.SelectionError)
+high = options(150_000)
+high.target_feerate = 2000.0
+try:
+    cs.select_coin_fifo(inputs, high)
+    assert False
+except cs.AbnormallyHighFeeRateError:
+    pass
+"#
+        ))
+        .unwrap();
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{
+    selectcoin::CoinSelector,
+    types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
+    utils::calculate_fee,
+};
+use alloc::vec::Vec;
+
+/// Default incremental relay feerate of Bitcoin Core, 1 sat/vB, in sats per weight unit.
+pub const DEFAULT_INCREMENTAL_RELAY_FEERATE: f32 = 0.25;
+
+/// Number of selections made to bring the fee of a replacement down to what BIP125 requires.
+const REPLACEMENT_ROUNDS: usize = 3;
+
+/// The transaction a replacement conflicts with.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplacedTransaction {
+    /// Indices of its inputs among the candidate inputs, which the replacement must spend too.
+    ///
+    /// An index out of range of the inputs fails the selection with `MustSpendOutOfRange`, as for
+    /// [`CoinSelectionOpt::must_spend`].
+    pub inputs: Vec<usize>,
+    /// Its absolute fee.
+    pub fee: u64,
+    /// Its weight.
+    pub weight: u64,
+    /// Incremental relay feerate of the nodes in sats per weight unit, positive and usually
+    /// [`DEFAULT_INCREMENTAL_RELAY_FEERATE`].
+    pub incremental_relay_feerate: f32,
+}
+
+impl ReplacedTransaction {
+    /// Returns the lowest fee a replacement of `weight` must pay.
+    ///
+    /// BIP125 rule 3 requires at least the fee of the replaced transaction, and rule 4 additionally the incremental
+    /// relay feerate over the whole replacement. Returns `InsufficientReplacementFee` if that fee doesn't fit in a `u64`,
+    /// as no inputs can pay it.
+    pub fn required_fee(&self, weight: u64) -> Result<u64, SelectionError> {
+        self.fee
+            .checked_add(calculate_fee(weight, self.incremental_relay_feerate)?)
+            .ok_or(SelectionError::InsufficientReplacementFee)
+    }
+
+    /// Returns the feerate at which a replacement of `weight` pays [`ReplacedTransaction::required_fee`], with a sat
+    /// to spare for rounding, and never pays less than the replaced transaction per weight unit.
+    fn required_feerate(&self, weight: u64) -> f32 {
+        let replaced_feerate = self.fee as f32 / self.weight.max(1) as f32;
+        let bumped_feerate = self.incremental_relay_feerate
+            + self.fee.saturating_add(1) as f32 / weight.max(1) as f32;
+        replaced_feerate.max(bumped_feerate)
+    }
+}
+
+/// Selects the inputs of a transaction replacing `replaced`, with the algorithms of [`CoinSelector::default`].
+///
+/// See [`CoinSelector::select_replacement`].
+pub fn select_replacement(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    replaced: &ReplacedTransaction,
+) -> Result<SelectionOutput, SelectionError> {
+    CoinSelector::default().select_replacement(inputs, options, replaced)
+}
+
+impl CoinSelector {
+    /// Selects the inputs of a transaction replacing `replaced`, following the BIP125 rules 3 and 4.
+    ///
+    /// The inputs of the replaced transaction are added to the must-spend inputs of `options`, and the selection pays at
+    /// least `target_feerate` and at least [`ReplacedTransaction::required_fee`] for the weight of the replacement,
+    /// including its change output. As the weight depends on the inputs selected, the selection is repeated with the
+    /// feerate the previous one needed, and the one paying the lowest fee is returned.
+    ///
+    /// Returns `MustSpendOutOfRange` if an index of the replaced or must-spend inputs is out of range of `inputs`. If no
+    /// selection pays the required fee, returns the error of the failed selection, such as `InsufficientFunds`, or
+    /// `InsufficientReplacementFee` if every round selected inputs without paying it.
+    pub fn select_replacement(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+        replaced: &ReplacedTransaction,
+    ) -> Result<SelectionOutput, SelectionError> {
+        let mut options = options.clone();
+        options.must_spend.extend_from_slice(&replaced.inputs);
+        // The selection loop below gives up on any error, so the indices are checked before computing the weight
+        if options
+            .must_spend
+            .iter()
+            .any(|&index| index >= inputs.len())
+        {
+            return Err(SelectionError::MustSpendOutOfRange);
+        }
+        options.must_spend.sort_unstable();
+        options.must_spend.dedup();
+
+        // The replacement is at least as heavy as its outputs and the replaced inputs
+        let min_weight = options.base_weight
+            + options
+                .must_spend
+                .iter()
+                .map(|&index| inputs[index].weight)
+                .sum::<u64>();
+        let target_feerate = options.target_feerate;
+        let mut feerate = target_feerate.max(replaced.required_feerate(min_weight));
+        // The selector reports invalid feerates as failed selections, check them first
+        calculate_fee(min_weight, feerate)?;
+        replaced.required_fee(min_weight)?;
+
+        let mut best: Option<SelectionOutput> = None;
+        for _ in 0..REPLACEMENT_ROUNDS {
+            options.target_feerate = feerate;
+            let selection_output = match self.select(inputs, &options) {
+                Ok(selection_output) => selection_output,
+                // A later round failing at a higher feerate keeps the best selection found so far
+                Err(error) if best.is_none() => return Err(error),
+                Err(_) => break,
+            };
+            let weight = options.base_weight
+                + selection_output.total_weight
+                + selection_output
+                    .change_value
+                    .map_or(0, |_| options.change_weight);
+            let next_feerate = target_feerate.max(replaced.required_feerate(weight));
+
+            let cheaper = match &best {
+                Some(best) => selection_output.fee < best.fee,
+                None => true,
+            };
+            if selection_output.fee >= replaced.required_fee(weight)? && cheaper {
+                best = Some(selection_output);
+            }
+            if next_feerate >= feerate {
+                break;
+            }
+            feerate = next_feerate;
+        }
+        best.ok_or(SelectionError::InsufficientReplacementFee)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        rbf::{select_replacement, ReplacedTransaction, DEFAULT_INCREMENTAL_RELAY_FEERATE},
+        types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError},
+    };
+    use alloc::{vec, vec::Vec};
+
+    fn setup_output_groups() -> Vec<OutputGroup> {
+        vec![
+            OutputGroup {
+                value: 60000,
+                weight: 272,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+            OutputGroup {
+                value: 30000,
+                weight: 272,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+            OutputGroup {
+                value: 20000,
+                weight: 272,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+            OutputGroup {
+                value: 5000,
+                weight: 272,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn setup_options(target_value: u64, target_feerate: f32) -> CoinSelectionOpt {
+        CoinSelectionOpt {
+            target_value,
+            target_feerate,
+            long_term_feerate: Some(0.5),
+            min_absolute_fee: 0,
+            base_weight: 166,
+            change_weight: 124,
+            change_cost: 200,
+            avg_input_weight: 272,
+            avg_output_weight: 124,
+            min_change_value: 500,
+            excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
+        }
+    }
+
+    /// The replaced transaction spent the first input to pay 50000 sats, with a change output, at 1 sat/wu.
+    fn setup_replaced() -> ReplacedTransaction {
+        ReplacedTransaction {
+            inputs: vec![0],
+            fee: 562,
+            weight: 562,
+            incremental_relay_feerate: DEFAULT_INCREMENTAL_RELAY_FEERATE,
+        }
+    }
+
+    #[test]
+    fn test_select_replacement() {
+        let inputs = setup_output_groups();
+        let replaced = setup_replaced();
+        // Fee bump of the same payment: the target feerate alone satisfies the rules
+        let selection_output =
+            select_replacement(&inputs, &setup_options(50000, 2.0), &replaced).unwrap();
+        assert!(selection_output.selected_inputs.contains(&0));
+        let weight =
+            166 + selection_output.total_weight + selection_output.change_value.map_or(0, |_| 124);
+        assert!(selection_output.fee >= replaced.required_fee(weight).unwrap());
+        assert!(selection_output.fee >= 2 * weight);
+
+        // A target feerate below the replaced one is raised to pay the replaced fee plus the relay fee
+        let selection_output =
+            select_replacement(&inputs, &setup_options(50000, 0.5), &replaced).unwrap();
+        assert!(selection_output.selected_inputs.contains(&0));
+        let weight =
+            166 + selection_output.total_weight + selection_output.change_value.map_or(0, |_| 124);
+        assert!(selection_output.fee >= replaced.required_fee(weight).unwrap());
+        assert!(selection_output.feerate >= 1.0);
+
+        // The replacement of a larger payment keeps the replaced input and adds others
+        let selection_output =
+            select_replacement(&inputs, &setup_options(80000, 2.0), &replaced).unwrap();
+        assert!(selection_output.selected_inputs.contains(&0));
+        assert!(selection_output.selected_inputs.len() > 1);
+    }
+
+    #[test]
+    fn test_select_replacement_insufficient_fee() {
+        let inputs = setup_output_groups();
+        // The replaced transaction paid nearly all of the inputs in fees
+        let replaced = ReplacedTransaction {
+            inputs: vec![0],
+            fee: 60000,
+            weight: 562,
+            incremental_relay_feerate: DEFAULT_INCREMENTAL_RELAY_FEERATE,
+        };
+        // The first round underpays, and the inputs can't fund the feerate the next one needs
+        let result = select_replacement(&inputs, &setup_options(50000, 2.0), &replaced);
+        assert_eq!(result, Err(SelectionError::InsufficientFunds));
+        // As does a first round that finds no selection
+        let result = select_replacement(
+            &inputs,
+            &setup_options(u32::MAX.into(), 2.0),
+            &setup_replaced(),
+        );
+        assert_eq!(result, Err(SelectionError::InsufficientFunds));
+
+        // Fee rate errors are reported as is
+        let result = select_replacement(&inputs, &setup_options(50000, 2000.0), &setup_replaced());
+        assert_eq!(result, Err(SelectionError::AbnormallyHighFeeRate));
+
+        // A fee that can't be paid
+        let replaced = ReplacedTransaction {
+            fee: u64::MAX,
+            ..setup_replaced()
+        };
+        assert_eq!(
+            replaced.required_fee(562),
+            Err(SelectionError::InsufficientReplacementFee)
+        );
+        // Paying it would take an abnormally high feerate
+        let result = select_replacement(&inputs, &setup_options(50000, 2.0), &replaced);
+        assert_eq!(result, Err(SelectionError::AbnormallyHighFeeRate));
+    }
+
+    #[test]
+    fn test_select_replacement_out_of_range() {
+        let inputs = setup_output_groups();
+        // A replaced input missing from the inputs
+        let replaced = ReplacedTransaction {
+            inputs: vec![0, 4],
+            ..setup_replaced()
+        };
+        let result = select_replacement(&inputs, &setup_options(50000, 2.0), &replaced);
+        assert_eq!(result, Err(SelectionError::MustSpendOutOfRange));
+
+        // A must-spend input missing from the inputs
+        let options = CoinSelectionOpt {
+            must_spend: vec![7],
+            ..setup_options(50000, 2.0)
+        };
+        let result = select_replacement(&inputs, &options, &setup_replaced());
+        assert_eq!(result, Err(SelectionError::MustSpendOutOfRange));
+    }
+}
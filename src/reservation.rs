@@ -0,0 +1,269 @@
+use crate::types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Thread-safe registry of the inputs reserved by in-flight transactions.
+///
+/// Concurrent sessions building transactions from the same pool of [`OutputGroup`]s share a [`CoinReservation`], and select
+/// through [`CoinReservation::select`]. The inputs of each selection are locked until they are released or the timeout
+/// expires, so two sessions never pick the same coins.
+///
+/// Indices refer to the shared pool of inputs, which must be the same slice, in the same order, for every session.
+#[derive(Debug)]
+pub struct CoinReservation {
+    timeout: Duration,
+    /// Reserved input indices with the instant their reservation expires.
+    reserved: Mutex<HashMap<usize, Instant>>,
+}
+
+impl CoinReservation {
+    /// Creates an empty reservation registry, where reservations expire after `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        CoinReservation {
+            timeout,
+            reserved: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs `selector` on `inputs` with the reserved inputs excluded, and reserves the inputs it selects.
+    ///
+    /// The registry stays locked while `selector` runs, so concurrent calls are serialized and can't select the same inputs.
+    /// Returns `InputReserved` without running `selector` if one of the must-spend inputs is reserved.
+    pub fn select<F>(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+        selector: F,
+    ) -> Result<SelectionOutput, SelectionError>
+    where
+        F: FnOnce(&[OutputGroup], &CoinSelectionOpt) -> Result<SelectionOutput, SelectionError>,
+    {
+        let mut reserved = self.reserved.lock().expect("Reservation lock poisoned");
+        let now = Instant::now();
+        reserved.retain(|_, expiry| *expiry > now);
+        if options
+            .must_spend
+            .iter()
+            .any(|index| reserved.contains_key(index))
+        {
+            return Err(SelectionError::InputReserved);
+        }
+
+        let mut options = options.clone();
+        options.excluded.extend(reserved.keys().copied());
+        let selection_output = selector(inputs, &options)?;
+
+        let expiry = now + self.timeout;
+        for &index in &selection_output.selected_inputs {
+            reserved.insert(index, expiry);
+        }
+        Ok(selection_output)
+    }
+
+    /// Reserves the inputs of `selection_output` until the timeout expires.
+    ///
+    /// Returns `InputReserved`, and reserves nothing, if one of the inputs is already reserved.
+    pub fn reserve(&self, selection_output: &SelectionOutput) -> Result<(), SelectionError> {
+        let mut reserved = self.reserved.lock().expect("Reservation lock poisoned");
+        let now = Instant::now();
+        reserved.retain(|_, expiry| *expiry > now);
+        if selection_output
+            .selected_inputs
+            .iter()
+            .any(|index| reserved.contains_key(index))
+        {
+            return Err(SelectionError::InputReserved);
+        }
+
+        let expiry = now + self.timeout;
+        for &index in &selection_output.selected_inputs {
+            reserved.insert(index, expiry);
+        }
+        Ok(())
+    }
+
+    /// Releases the inputs of `selection_output`, for instance when its transaction is abandoned.
+    pub fn release(&self, selection_output: &SelectionOutput) {
+        self.release_inputs(&selection_output.selected_inputs);
+    }
+
+    /// Releases the inputs at `indices`.
+    pub fn release_inputs(&self, indices: &[usize]) {
+        let mut reserved = self.reserved.lock().expect("Reservation lock poisoned");
+        for index in indices {
+            reserved.remove(index);
+        }
+    }
+
+    /// Returns whether the input at `index` is currently reserved.
+    pub fn is_reserved(&self, index: usize) -> bool {
+        let reserved = self.reserved.lock().expect("Reservation lock poisoned");
+        matches!(reserved.get(&index), Some(expiry) if *expiry > Instant::now())
+    }
+
+    /// Returns the indices of the currently reserved inputs, in ascending order.
+    pub fn reserved_inputs(&self) -> Vec<usize> {
+        let reserved = self.reserved.lock().expect("Reservation lock poisoned");
+        let now = Instant::now();
+        let mut indices: Vec<usize> = reserved
+            .iter()
+            .filter(|(_, expiry)| **expiry > now)
+            .map(|(&index, _)| index)
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        algorithms::fifo::select_coin_fifo,
+        reservation::CoinReservation,
+        selectcoin::select_coin,
+        types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError},
+    };
+    use std::{thread, time::Duration};
+
+    fn setup_output_groups() -> Vec<OutputGroup> {
+        (0..10)
+            .map(|_| OutputGroup {
+                value: 1000,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn setup_options(target_value: u64) -> CoinSelectionOpt {
+        CoinSelectionOpt {
+            target_value,
+            target_feerate: 0.4, // Simplified feerate
+            long_term_feerate: Some(0.4),
+            min_absolute_fee: 0,
+            base_weight: 10,
+            change_weight: 50,
+            change_cost: 10,
+            avg_input_weight: 20,
+            avg_output_weight: 10,
+            min_change_value: 500,
+            excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
+        }
+    }
+
+    #[test]
+    fn test_reservation_excludes_reserved_inputs() {
+        let inputs = setup_output_groups();
+        let options = setup_options(1500);
+        let reservation = CoinReservation::new(Duration::from_secs(60));
+
+        let first = reservation
+            .select(&inputs, &options, select_coin_fifo)
+            .unwrap();
+        assert!(first
+            .selected_inputs
+            .iter()
+            .all(|&index| reservation.is_reserved(index)));
+        let second = reservation
+            .select(&inputs, &options, select_coin_fifo)
+            .unwrap();
+        assert!(second
+            .selected_inputs
+            .iter()
+            .all(|index| !first.selected_inputs.contains(index)));
+
+        // Released inputs can be selected again
+        reservation.release(&first);
+        let third = reservation
+            .select(&inputs, &options, select_coin_fifo)
+            .unwrap();
+        assert_eq!(first.selected_inputs, third.selected_inputs);
+
+        // Once everything is reserved, the remaining inputs can't fund the target
+        let options = setup_options(3000);
+        let _ = reservation.select(&inputs, &options, select_coin_fifo);
+        let result = reservation.select(&inputs, &options, select_coin_fifo);
+        assert!(matches!(result, Err(SelectionError::InsufficientFunds)));
+    }
+
+    #[test]
+    fn test_reservation_conflicts() {
+        let inputs = setup_output_groups();
+        let options = setup_options(1500);
+        let reservation = CoinReservation::new(Duration::from_secs(60));
+        let first = reservation
+            .select(&inputs, &options, select_coin_fifo)
+            .unwrap();
+
+        // A reserved input can't be forced into another selection
+        let mut must_spend_options = options.clone();
+        must_spend_options.must_spend = vec![first.selected_inputs[0]];
+        let result = reservation.select(&inputs, &must_spend_options, select_coin_fifo);
+        assert_eq!(result, Err(SelectionError::InputReserved));
+
+        // Nor reserved again, and the free inputs of a conflicting selection stay free
+        let mut conflicting = select_coin_fifo(&inputs, &options).unwrap();
+        conflicting.selected_inputs.push(9);
+        assert_eq!(
+            reservation.reserve(&conflicting),
+            Err(SelectionError::InputReserved)
+        );
+        assert!(!reservation.is_reserved(9));
+
+        reservation.release(&first);
+        assert_eq!(reservation.reserve(&conflicting), Ok(()));
+        assert_eq!(reservation.reserved_inputs(), conflicting.selected_inputs);
+    }
+
+    #[test]
+    fn test_reservation_timeout() {
+        let inputs = setup_output_groups();
+        let options = setup_options(1500);
+        let reservation = CoinReservation::new(Duration::ZERO);
+        let selection_output = reservation
+            .select(&inputs, &options, select_coin_fifo)
+            .unwrap();
+        // The reservation expires immediately
+        assert!(!reservation.is_reserved(selection_output.selected_inputs[0]));
+        assert!(reservation.reserved_inputs().is_empty());
+    }
+
+    #[test]
+    fn test_reservation_concurrent_sessions() {
+        let inputs = setup_output_groups();
+        let options = setup_options(1500);
+        let reservation = CoinReservation::new(Duration::from_secs(60));
+
+        let selections: Vec<Vec<usize>> = thread::scope(|s| {
+            let handles: Vec<_> = (0..3)
+                .map(|_| {
+                    s.spawn(|| {
+                        reservation
+                            .select(&inputs, &options, select_coin)
+                            .unwrap()
+                            .selected_inputs
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let mut all_selected: Vec<usize> = selections.concat();
+        let selected_count = all_selected.len();
+        all_selected.sort_unstable();
+        all_selected.dedup();
+        assert_eq!(all_selected.len(), selected_count);
+        assert_eq!(reservation.reserved_inputs(), all_selected);
+    }
+}
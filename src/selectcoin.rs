@@ -1,84 +1,326 @@
 use crate::{
     algorithms::{
-        bnb::select_coin_bnb, fifo::select_coin_fifo, knapsack::select_coin_knapsack,
-        lowestlarger::select_coin_lowestlarger, srd::select_coin_srd,
+        bnb::BranchAndBound, coingrinder::CoinGrinder, fifo::Fifo, knapsack::Knapsack,
+        lowestlarger::LowestLarger, srd::SingleRandomDraw, CoinSelectionAlgorithm,
+    },
+    types::{
+        AlgorithmOutcome, CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput,
+        SelectionReport,
     },
-    types::{CoinSelectionOpt, OutputGroup, SelectionError, SelectionOutput},
-};
-use std::{
-    sync::{Arc, Mutex},
-    thread,
 };
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use core::fmt;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+use std::thread;
 
-/// The global coin selection API that applies all algorithms and produces the result with the lowest [WasteMetric].
+/// The global coin selection API that applies all algorithms and produces the result with the lowest [`WasteMetric`](crate::types::WasteMetric).
 ///
 /// At least one selection solution should be found.
-type CoinSelectionFn =
-    fn(&[OutputGroup], &CoinSelectionOpt) -> Result<SelectionOutput, SelectionError>;
-
-#[derive(Debug)]
-struct SharedState {
-    result: Result<SelectionOutput, SelectionError>,
-    any_success: bool,
+/// Without the `std` feature SRD and Knapsack have no source of randomness and always fail here, so only the
+/// deterministic algorithms compete. Use [`select_coin_with_seed`] to include them.
+pub fn select_coin(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+) -> Result<SelectionOutput, SelectionError> {
+    CoinSelector::default().select(inputs, options)
 }
 
-pub fn select_coin(
+/// Same as [`select_coin`], with every randomized algorithm seeded from `seed`.
+///
+/// The same seed, inputs and options always produce the same result, which makes a selection reproducible.
+pub fn select_coin_with_seed(
     inputs: &[OutputGroup],
     options: &CoinSelectionOpt,
+    seed: u64,
 ) -> Result<SelectionOutput, SelectionError> {
-    let algorithms: Vec<CoinSelectionFn> = vec![
-        select_coin_bnb,
-        select_coin_fifo,
-        select_coin_lowestlarger,
-        select_coin_srd,
-        select_coin_knapsack, // Future algorithms can be added here
-    ];
-    // Shared result for all threads
-    let best_result = Arc::new(Mutex::new(SharedState {
-        result: Err(SelectionError::NoSolutionFound),
-        any_success: false,
-    }));
-    for &algorithm in &algorithms {
-        let best_result_clone = Arc::clone(&best_result);
-        thread::scope(|s| {
-            s.spawn(|| {
-                let result = algorithm(inputs, options);
-                let mut state = best_result_clone.lock().unwrap();
-                match result {
-                    Ok(selection_output) => {
-                        if match &state.result {
-                            Ok(current_best) => selection_output.waste.0 < current_best.waste.0,
-                            Err(_) => true,
-                        } {
-                            state.result = Ok(selection_output);
-                            state.any_success = true;
-                        }
+    CoinSelector::default().select_with_seed(inputs, options, seed)
+}
+
+/// Same as [`select_coin`], returning the outcome of every algorithm instead of only the selection with the lowest waste.
+///
+/// The selection of [`select_coin`] is [`SelectionReport::best`].
+pub fn select_coin_report(inputs: &[OutputGroup], options: &CoinSelectionOpt) -> SelectionReport {
+    CoinSelector::default().report(inputs, options)
+}
+
+/// Same as [`select_coin_report`], with every randomized algorithm seeded from `seed` like [`select_coin_with_seed`].
+pub fn select_coin_report_with_seed(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    seed: u64,
+) -> SelectionReport {
+    CoinSelector::default().report_with_seed(inputs, options, seed)
+}
+
+/// A configurable set of [`CoinSelectionAlgorithm`]s whose results are compared by [`WasteMetric`](crate::types::WasteMetric).
+///
+/// [`CoinSelector::default`] contains all the algorithms of this library, [`CoinSelector::new`] starts empty.
+/// Algorithms can then be added, removed and reordered. When two algorithms produce the same waste,
+/// the result of the one registered first is returned.
+pub struct CoinSelector {
+    algorithms: Vec<Box<dyn CoinSelectionAlgorithm>>,
+}
+
+impl CoinSelector {
+    /// Creates a selector without any algorithm.
+    pub fn new() -> Self {
+        CoinSelector {
+            algorithms: Vec::new(),
+        }
+    }
+
+    /// Registers an algorithm after the ones already present.
+    pub fn add_algorithm<A: CoinSelectionAlgorithm + 'static>(mut self, algorithm: A) -> Self {
+        self.algorithms.push(Box::new(algorithm));
+        self
+    }
+
+    /// Removes every algorithm registered under `name`.
+    pub fn remove_algorithm(mut self, name: &str) -> Self {
+        self.algorithms.retain(|algorithm| algorithm.name() != name);
+        self
+    }
+
+    /// Reorders the algorithms following `names`.
+    ///
+    /// Algorithms not listed in `names` keep their relative order and are placed after the listed ones.
+    pub fn reorder(mut self, names: &[&str]) -> Self {
+        self.algorithms.sort_by_key(|algorithm| {
+            names
+                .iter()
+                .position(|&name| name == algorithm.name())
+                .unwrap_or(names.len())
+        });
+        self
+    }
+
+    /// Names of the registered algorithms, in order.
+    pub fn algorithm_names(&self) -> Vec<&str> {
+        self.algorithms
+            .iter()
+            .map(|algorithm| algorithm.name())
+            .collect()
+    }
+
+    /// Runs all the registered algorithms and returns the result with the lowest [`WasteMetric`](crate::types::WasteMetric).
+    ///
+    /// Returns `MustSpendOutOfRange` or else `InsufficientFunds` if no algorithm succeeded and at least one of them
+    /// reported it, and `NoSolutionFound` otherwise.
+    ///
+    /// Without the `std` feature the randomized algorithms, SRD and Knapsack, fail with `NoSolutionFound` here. Use
+    /// [`CoinSelector::select_with_seed`] to run them.
+    pub fn select(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+    ) -> Result<SelectionOutput, SelectionError> {
+        self.report(inputs, options).into_best()
+    }
+
+    /// Same as [`CoinSelector::select`], with every algorithm given its own rng derived from `seed`.
+    ///
+    /// The same seed, algorithms, inputs and options always produce the same result.
+    pub fn select_with_seed(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+        seed: u64,
+    ) -> Result<SelectionOutput, SelectionError> {
+        self.report_with_seed(inputs, options, seed).into_best()
+    }
+
+    /// Runs all the registered algorithms and returns the outcome of each, with the time it took.
+    pub fn report(&self, inputs: &[OutputGroup], options: &CoinSelectionOpt) -> SelectionReport {
+        SelectionReport {
+            outcomes: run_algorithms(&self.algorithms, inputs, options, None),
+        }
+    }
+
+    /// Same as [`CoinSelector::report`], with every algorithm given its own rng derived from `seed`.
+    ///
+    /// The outcomes are the ones [`CoinSelector::select_with_seed`] compares for the same seed.
+    pub fn report_with_seed(
+        &self,
+        inputs: &[OutputGroup],
+        options: &CoinSelectionOpt,
+        seed: u64,
+    ) -> SelectionReport {
+        // Seeds are drawn upfront, in order, so they don't depend on how the algorithms are scheduled
+        let mut seeder = StdRng::seed_from_u64(seed);
+        let seeds: Vec<u64> = self.algorithms.iter().map(|_| seeder.gen()).collect();
+        SelectionReport {
+            outcomes: run_algorithms(&self.algorithms, inputs, options, Some(&seeds)),
+        }
+    }
+}
+
+impl Default for CoinSelector {
+    fn default() -> Self {
+        CoinSelector::new()
+            .add_algorithm(BranchAndBound)
+            .add_algorithm(Fifo)
+            .add_algorithm(LowestLarger)
+            .add_algorithm(SingleRandomDraw)
+            .add_algorithm(Knapsack)
+            .add_algorithm(CoinGrinder) // Future algorithms can be added here
+    }
+}
+
+impl fmt::Debug for CoinSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoinSelector")
+            .field("algorithms", &self.algorithm_names())
+            .finish()
+    }
+}
+
+/// Runs every algorithm concurrently, one thread each, and waits for all of them to finish.
+///
+/// The results are returned in the same order as the algorithms.
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+fn run_algorithms(
+    algorithms: &[Box<dyn CoinSelectionAlgorithm>],
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    seeds: Option<&[u64]>,
+) -> Vec<AlgorithmOutcome> {
+    thread::scope(|s| {
+        let handles: Vec<_> = algorithms
+            .iter()
+            .enumerate()
+            .map(|(i, algorithm)| {
+                let seed = seeds.map(|seeds| seeds[i]);
+                s.spawn(move || run_algorithm(algorithm.as_ref(), inputs, options, seed))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Coin selection thread panicked"))
+            .collect()
+    })
+}
+
+/// Runs every algorithm one after another on the calling thread.
+///
+/// Used when the `parallel` feature is disabled and on targets without threads, such as wasm32.
+#[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+fn run_algorithms(
+    algorithms: &[Box<dyn CoinSelectionAlgorithm>],
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    seeds: Option<&[u64]>,
+) -> Vec<AlgorithmOutcome> {
+    algorithms
+        .iter()
+        .enumerate()
+        .map(|(i, algorithm)| {
+            run_algorithm(
+                algorithm.as_ref(),
+                inputs,
+                options,
+                seeds.map(|seeds| seeds[i]),
+            )
+        })
+        .collect()
+}
+
+/// Runs a single algorithm, seeding its rng when a seed is given, and times it where the clock is available.
+fn run_algorithm(
+    algorithm: &dyn CoinSelectionAlgorithm,
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    seed: Option<u64>,
+) -> AlgorithmOutcome {
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    let start = std::time::Instant::now();
+    let result = match seed {
+        Some(seed) => algorithm.select_with_rng(inputs, options, &mut StdRng::seed_from_u64(seed)),
+        None => algorithm.select(inputs, options),
+    };
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    let duration = Some(start.elapsed());
+    #[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+    let duration = None;
+    AlgorithmOutcome {
+        algorithm: algorithm.name().to_string(),
+        result,
+        duration,
+    }
+}
+
+impl SelectionReport {
+    /// Returns the successful selection with the lowest waste, the earliest one winning ties.
+    ///
+    /// If no algorithm succeeded, returns `MustSpendOutOfRange` or else `InsufficientFunds` when any algorithm reported
+    /// it, and `NoSolutionFound` otherwise.
+    pub fn best(&self) -> Result<&SelectionOutput, SelectionError> {
+        let index = self.lowest_waste()?;
+        Ok(self.outcomes[index]
+            .result
+            .as_ref()
+            .expect("The lowest waste outcome is a selection"))
+    }
+
+    /// Same as [`SelectionReport::best`], taking the selection out of the report.
+    pub fn into_best(mut self) -> Result<SelectionOutput, SelectionError> {
+        let index = self.lowest_waste()?;
+        self.outcomes.swap_remove(index).result
+    }
+
+    /// Returns the index of the successful outcome with the lowest waste, or the error summing up the failures.
+    fn lowest_waste(&self) -> Result<usize, SelectionError> {
+        let mut best: Result<(usize, i64), SelectionError> = Err(SelectionError::NoSolutionFound);
+        for (index, outcome) in self.outcomes.iter().enumerate() {
+            match &outcome.result {
+                Ok(selection_output) => {
+                    if match best {
+                        Ok((_, lowest_waste)) => selection_output.waste.0 < lowest_waste,
+                        Err(_) => true,
+                    } {
+                        best = Ok((index, selection_output.waste.0));
                     }
-                    Err(e) => {
-                        if e == SelectionError::InsufficientFunds && !state.any_success {
-                            // Only set to InsufficientFunds if no algorithm succeeded
-                            state.result = Err(SelectionError::InsufficientFunds);
-                        }
+                }
+                Err(SelectionError::MustSpendOutOfRange) => {
+                    // Invalid options fail every algorithm, and no other error explains the failure
+                    if best.is_err() {
+                        best = Err(SelectionError::MustSpendOutOfRange);
                     }
                 }
-            });
-        });
+                Err(SelectionError::InsufficientFunds) => {
+                    // Only set to InsufficientFunds if no algorithm succeeded
+                    if best == Err(SelectionError::NoSolutionFound) {
+                        best = Err(SelectionError::InsufficientFunds);
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+        best.map(|(index, _)| index)
     }
-    // Extract the result from the shared state
-    Arc::try_unwrap(best_result)
-        .expect("Arc unwrap failed")
-        .into_inner()
-        .expect("Mutex lock failed")
-        .result
 }
 
 #[cfg(test)]
 mod test {
 
     use crate::{
-        selectcoin::select_coin,
-        types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError},
+        algorithms::{
+            bnb::BranchAndBound, coingrinder::CoinGrinder, fifo::Fifo, knapsack::Knapsack,
+            lowestlarger::LowestLarger, srd::SingleRandomDraw, CoinSelectionAlgorithm,
+        },
+        selectcoin::{
+            select_coin, select_coin_report, select_coin_report_with_seed, select_coin_with_seed,
+            CoinSelector,
+        },
+        types::{
+            Ancestors, CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError,
+            SelectionOutput, WasteMetric,
+        },
+        utils::create_selection_output,
     };
+    use alloc::{boxed::Box, vec, vec::Vec};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::dbg;
 
     fn setup_basic_output_groups() -> Vec<OutputGroup> {
         vec![
@@ -87,18 +329,21 @@ mod test {
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 2000,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 3000,
                 weight: 300,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ]
     }
@@ -116,6 +361,8 @@ mod test {
             avg_output_weight: 10,
             min_change_value: 500,
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         }
     }
 
@@ -129,6 +376,256 @@ mod test {
         assert!(!selection_output.selected_inputs.is_empty());
     }
 
+    #[test]
+    fn test_select_coin_returns_lowest_waste() {
+        let inputs = setup_basic_output_groups();
+        let options = setup_options(1500);
+        let selection_output = select_coin(&inputs, &options).unwrap();
+        // Every algorithm has finished before the best result is picked
+        let deterministic_algorithms: Vec<Box<dyn CoinSelectionAlgorithm>> = vec![
+            Box::new(BranchAndBound),
+            Box::new(Fifo),
+            Box::new(LowestLarger),
+            Box::new(CoinGrinder),
+        ];
+        for algorithm in deterministic_algorithms {
+            if let Ok(result) = algorithm.select(&inputs, &options) {
+                assert!(selection_output.waste.0 <= result.waste.0);
+            }
+        }
+    }
+
+    /// Selects every input with no waste, so it always wins the comparison.
+    struct SelectAll;
+
+    impl CoinSelectionAlgorithm for SelectAll {
+        fn name(&self) -> &str {
+            "selectall"
+        }
+
+        fn select(
+            &self,
+            inputs: &[OutputGroup],
+            options: &CoinSelectionOpt,
+        ) -> Result<SelectionOutput, SelectionError> {
+            create_selection_output(
+                inputs,
+                options,
+                (0..inputs.len()).collect(),
+                WasteMetric(0),
+                self.name(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_coin_selector_configuration() {
+        let selector = CoinSelector::default();
+        assert_eq!(
+            selector.algorithm_names(),
+            vec![
+                "bnb",
+                "fifo",
+                "lowestlarger",
+                "srd",
+                "knapsack",
+                "coingrinder"
+            ]
+        );
+
+        let selector = selector
+            .remove_algorithm("srd")
+            .remove_algorithm("knapsack")
+            .add_algorithm(SelectAll)
+            .reorder(&["selectall", "coingrinder"]);
+        assert_eq!(
+            selector.algorithm_names(),
+            vec!["selectall", "coingrinder", "bnb", "fifo", "lowestlarger"]
+        );
+
+        // The custom algorithm takes part in the waste comparison
+        let inputs = setup_basic_output_groups();
+        let options = setup_options(1500);
+        let selection_output = selector.select(&inputs, &options).unwrap();
+        assert_eq!(selection_output.selected_inputs, vec![0, 1, 2]);
+        assert_eq!(selection_output.algorithm, "selectall");
+
+        // A selector without algorithms finds no solution
+        let result = CoinSelector::new().select(&inputs, &options);
+        assert!(matches!(result, Err(SelectionError::NoSolutionFound)));
+    }
+
+    #[test]
+    fn test_select_coin_with_seed_is_reproducible() {
+        // Identical inputs leave the randomized algorithms many equivalent choices
+        let inputs: Vec<OutputGroup> = (0..20)
+            .map(|_| OutputGroup {
+                value: 1000,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            })
+            .collect();
+        let options = setup_options(5000);
+        let selector = CoinSelector::new()
+            .add_algorithm(SingleRandomDraw)
+            .add_algorithm(Knapsack);
+        let first = selector.select_with_seed(&inputs, &options, 1234).unwrap();
+        let second = selector.select_with_seed(&inputs, &options, 1234).unwrap();
+        assert_eq!(first.selected_inputs, second.selected_inputs);
+
+        let first = select_coin_with_seed(&inputs, &options, 1234).unwrap();
+        let second = select_coin_with_seed(&inputs, &options, 1234).unwrap();
+        assert_eq!(first.selected_inputs, second.selected_inputs);
+    }
+
+    #[test]
+    fn test_select_coin_report() {
+        let inputs = setup_basic_output_groups();
+        let options = setup_options(1500);
+        let report = select_coin_report_with_seed(&inputs, &options, 1234);
+        let names: Vec<&str> = report
+            .outcomes
+            .iter()
+            .map(|outcome| outcome.algorithm.as_str())
+            .collect();
+        assert_eq!(names, CoinSelector::default().algorithm_names());
+        #[cfg(feature = "std")]
+        assert!(report
+            .outcomes
+            .iter()
+            .all(|outcome| outcome.duration.is_some()));
+        // The best selection of the report is the one of select_coin
+        let best = report.best().unwrap();
+        assert!(report.outcomes.iter().all(|outcome| match &outcome.result {
+            Ok(selection_output) => best.waste.0 <= selection_output.waste.0,
+            Err(_) => true,
+        }));
+        assert_eq!(
+            report.into_best().unwrap(),
+            select_coin_with_seed(&inputs, &options, 1234).unwrap()
+        );
+
+        // Every failure is kept with its own error
+        let report = select_coin_report(&inputs, &setup_options(7000));
+        assert_eq!(report.outcomes.len(), 6);
+        assert!(report
+            .outcomes
+            .iter()
+            .all(|outcome| outcome.result.is_err()));
+        assert!(matches!(
+            report.best(),
+            Err(SelectionError::InsufficientFunds)
+        ));
+    }
+
+    #[test]
+    fn test_select_coin_must_spend() {
+        let inputs = setup_basic_output_groups();
+        let mut options = setup_options(2500);
+        options.must_spend = vec![0];
+        // Every algorithm keeps the must-spend input, and the indices refer to the given inputs
+        let algorithms: Vec<Box<dyn CoinSelectionAlgorithm>> = vec![
+            Box::new(BranchAndBound),
+            Box::new(Fifo),
+            Box::new(LowestLarger),
+            Box::new(SingleRandomDraw),
+            Box::new(Knapsack),
+            Box::new(CoinGrinder),
+        ];
+        for algorithm in &algorithms {
+            if let Ok(result) = algorithm.select(&inputs, &options) {
+                assert!(result.selected_inputs.contains(&0));
+                assert!(result.selected_inputs.iter().all(|&i| i < inputs.len()));
+            }
+        }
+        let selection_output = select_coin(&inputs, &options).unwrap();
+        assert!(selection_output.selected_inputs.contains(&0));
+        assert!(selection_output.total_value >= options.target_value + selection_output.fee);
+
+        // Must-spend inputs funding the target on their own make the whole selection
+        options.target_value = 1000;
+        options.must_spend = vec![2];
+        let selection_output = select_coin(&inputs, &options).unwrap();
+        assert_eq!(selection_output.selected_inputs, vec![2]);
+
+        // A must-spend input missing from the inputs can't be spent
+        options.must_spend = vec![3];
+        for algorithm in &algorithms {
+            assert_eq!(
+                algorithm.select_with_rng(&inputs, &options, &mut StdRng::seed_from_u64(0)),
+                Err(SelectionError::MustSpendOutOfRange)
+            );
+        }
+        assert_eq!(
+            select_coin(&inputs, &options),
+            Err(SelectionError::MustSpendOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_select_coin_ancestor_bump() {
+        // The first input was created by a transaction paying 100 sats of the 400 needed at the target feerate
+        let mut inputs = setup_basic_output_groups();
+        inputs[0].value = 2300;
+        inputs[0].weight = 300;
+        inputs[0].ancestors = Some(Ancestors {
+            weight: 1000,
+            fee: 100,
+        });
+        let bump = 300;
+        let mut options = setup_options(1500);
+        let algorithms: Vec<Box<dyn CoinSelectionAlgorithm>> = vec![
+            Box::new(BranchAndBound),
+            Box::new(Fifo),
+            Box::new(LowestLarger),
+            Box::new(SingleRandomDraw),
+            Box::new(Knapsack),
+            Box::new(CoinGrinder),
+        ];
+        for algorithm in &algorithms {
+            if let Ok(result) = algorithm.select(&inputs, &options) {
+                // The fee of a selection spending the first input includes the bump of its ancestors
+                let tx_weight = options.base_weight
+                    + result.total_weight
+                    + result.change_value.map_or(0, |_| options.change_weight);
+                let min_fee = (tx_weight as f32 * options.target_feerate).ceil() as u64;
+                if result.selected_inputs.contains(&0) {
+                    assert!(result.fee >= min_fee + bump);
+                }
+                assert_eq!(
+                    result.total_value,
+                    options.target_value + result.fee + result.change_value.unwrap_or(0)
+                );
+            }
+        }
+
+        // Confirmed, the oldest input funds the target on its own, but not once its ancestors are bumped
+        for (index, input) in inputs.iter_mut().enumerate() {
+            input.creation_sequence = Some(index as u32);
+        }
+        let mut confirmed_inputs = inputs.clone();
+        confirmed_inputs[0].ancestors = None;
+        let selection_output = Fifo.select(&confirmed_inputs, &options).unwrap();
+        assert_eq!(selection_output.selected_inputs, vec![0]);
+        let selection_output = Fifo.select(&inputs, &options).unwrap();
+        assert_eq!(selection_output.selected_inputs, vec![0, 1]);
+
+        // Spending it anyway makes the waste and the fee pay for the bump
+        options.target_value = 1000;
+        options.must_spend = vec![0];
+        let bumped_output = select_coin(&inputs, &options).unwrap();
+        let confirmed_output = select_coin(&confirmed_inputs, &options).unwrap();
+        assert_eq!(bumped_output.selected_inputs, vec![0]);
+        assert_eq!(confirmed_output.selected_inputs, vec![0]);
+        assert_eq!(bumped_output.fee, confirmed_output.fee + bump);
+        assert_eq!(
+            bumped_output.waste.0,
+            confirmed_output.waste.0 + bump as i64
+        );
+    }
+
     #[test]
     fn test_select_coin_insufficient_funds() {
         let inputs = setup_basic_output_groups();
@@ -146,24 +643,28 @@ mod test {
                 weight: 50,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 1500,
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 2000,
                 weight: 200,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 1000,
                 weight: 75,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ];
 
@@ -180,6 +681,8 @@ mod test {
             avg_output_weight: 25,
             min_change_value: 500,
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         };
 
         // Call the select_coin function, which should internally use the lowest_larger algorithm
@@ -204,30 +707,35 @@ mod test {
                 weight: 1,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 2500,
                 weight: 1,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 3000,
                 weight: 1,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 1000,
                 weight: 1,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 500,
                 weight: 1,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ];
 
@@ -244,6 +752,8 @@ mod test {
             min_change_value: 500,
             long_term_feerate: Some(0.5),
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         };
 
         let selection_result = select_coin(&inputs, &options).unwrap();
@@ -274,6 +784,7 @@ mod test {
                     weight: 100,
                     input_count: 1,
                     creation_sequence: None,
+                    ..Default::default()
                 })
                 .collect()
         }
@@ -290,6 +801,8 @@ mod test {
             min_change_value: 400,
             long_term_feerate: Some(0.5),
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         };
 
         let inputs_case = create_fifo_inputs(vec![80000, 70000, 60000, 50000, 40000, 30000]);
@@ -307,30 +820,35 @@ mod test {
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 250000,
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 300000,
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 100000,
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
             OutputGroup {
                 value: 50000,
                 weight: 100,
                 input_count: 1,
                 creation_sequence: None,
+                ..Default::default()
             },
         ];
         let opt = CoinSelectionOpt {
@@ -345,6 +863,8 @@ mod test {
             min_change_value: 400,
             long_term_feerate: Some(0.5),
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         };
         let ans = select_coin(&inputs, &opt);
 
@@ -0,0 +1,319 @@
+use crate::{
+    selectcoin::CoinSelector,
+    types::{CoinSelectionOpt, ExcessStrategy, OutputGroup},
+    utils::calculate_fee,
+    weight::{self, OutputType},
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// An event of a [`Scenario`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SimulationEvent {
+    /// A payment of `value` sats is received, adding an output to the pool.
+    Incoming(u64),
+    /// A payment of `value` sats is sent, funded by a selection from the pool.
+    Outgoing(u64),
+    /// The feerate, in sats per weight unit, of the following payments.
+    Feerate(f32),
+}
+
+/// A sequence of incoming payments, outgoing payments and feerate changes, replayed by [`simulate`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scenario {
+    /// The events, in the order they happen.
+    pub events: Vec<SimulationEvent>,
+}
+
+/// A line of a scenario file that can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioError {
+    /// Number of the line, starting at 1.
+    pub line: usize,
+    /// Content of the line.
+    pub content: String,
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid scenario line {}: {}", self.line, self.content)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ScenarioError {}
+
+impl Scenario {
+    /// Parses a scenario file, made of one `incoming,<sats>`, `outgoing,<sats>` or `feerate,<sats per weight unit>` event
+    /// per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self, ScenarioError> {
+        let mut events = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let event = match line.split_once(',') {
+                Some((kind, value)) => match (kind.trim(), value.trim()) {
+                    ("incoming", value) => value.parse().ok().map(SimulationEvent::Incoming),
+                    ("outgoing", value) => value.parse().ok().map(SimulationEvent::Outgoing),
+                    ("feerate", value) => value.parse().ok().map(SimulationEvent::Feerate),
+                    _ => None,
+                },
+                None => None,
+            };
+            match event {
+                Some(event) => events.push(event),
+                None => {
+                    return Err(ScenarioError {
+                        line: index + 1,
+                        content: line.to_string(),
+                    })
+                }
+            }
+        }
+        Ok(Scenario { events })
+    }
+}
+
+/// Parameters of the wallet of a simulation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimulationConfig {
+    /// Weight of an input spending an output of the pool, received payments and change alike.
+    pub input_weight: u64,
+    /// Weight of a transaction without its inputs and change output: the header and the payment output.
+    pub base_weight: u64,
+    /// Weight of a change output.
+    pub change_weight: u64,
+    /// Long term feerate of the waste metric, which is also the feerate until the first feerate event.
+    pub long_term_feerate: f32,
+    /// Smallest change output created.
+    pub min_change_value: u64,
+}
+
+impl Default for SimulationConfig {
+    /// A P2WPKH wallet with a long term feerate of 10 sats per virtual byte.
+    fn default() -> Self {
+        // Version, lock time, input and output counts, and the segwit marker and flag
+        let header_weight = (4 + 4 + 1 + 1) * 4 + 2;
+        SimulationConfig {
+            input_weight: weight::p2wpkh_input_weight(),
+            base_weight: header_weight + OutputType::P2wpkh.weight(),
+            change_weight: OutputType::P2wpkh.weight(),
+            long_term_feerate: 2.5,
+            min_change_value: 294,
+        }
+    }
+}
+
+/// Metrics of a simulated wallet lifetime.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimulationReport {
+    /// Number of outgoing payments made.
+    pub payments: usize,
+    /// Number of outgoing payments the selector couldn't fund, which are skipped.
+    pub failed_payments: usize,
+    /// Total fees paid by the payments, including the excess of changeless transactions.
+    pub total_fees: u64,
+    /// Cumulative waste of the selections.
+    pub cumulative_waste: i64,
+    /// Number of outputs left in the pool.
+    pub final_utxo_count: usize,
+    /// Value of the outputs left in the pool.
+    pub final_balance: u64,
+    /// Average number of inputs spent by a payment.
+    pub average_inputs: f32,
+    /// Share of the payments without change output.
+    pub changeless_rate: f32,
+}
+
+/// Replays `scenario` against an initially empty pool, funding each outgoing payment with a selection of `selector`.
+///
+/// Received payments and change outputs join the pool, with their event index as creation sequence. The selector runs
+/// with seeds drawn from `seed`, so the same scenario, selector, configuration and seed always produce the same report.
+pub fn simulate(
+    scenario: &Scenario,
+    selector: &CoinSelector,
+    config: &SimulationConfig,
+    seed: u64,
+) -> SimulationReport {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pool: Vec<OutputGroup> = Vec::new();
+    let mut feerate = config.long_term_feerate;
+    let mut report = SimulationReport {
+        payments: 0,
+        failed_payments: 0,
+        total_fees: 0,
+        cumulative_waste: 0,
+        final_utxo_count: 0,
+        final_balance: 0,
+        average_inputs: 0.0,
+        changeless_rate: 0.0,
+    };
+    let mut total_inputs = 0;
+    let mut changeless = 0;
+
+    for (index, event) in scenario.events.iter().enumerate() {
+        let creation_sequence = Some(index as u32);
+        match *event {
+            SimulationEvent::Incoming(value) => pool.push(OutputGroup {
+                value,
+                weight: config.input_weight,
+                input_count: 1,
+                creation_sequence,
+                ancestors: None,
+            }),
+            SimulationEvent::Feerate(rate) => feerate = rate,
+            SimulationEvent::Outgoing(value) => {
+                let options = payment_options(config, value, feerate);
+                let selection_output = match selector.select_with_seed(&pool, &options, rng.gen()) {
+                    Ok(selection_output) => selection_output,
+                    Err(_) => {
+                        report.failed_payments += 1;
+                        continue;
+                    }
+                };
+                report.payments += 1;
+                report.total_fees += selection_output.fee;
+                report.cumulative_waste += selection_output.waste.0;
+                total_inputs += selection_output
+                    .selected_inputs
+                    .iter()
+                    .map(|&i| pool[i].input_count)
+                    .sum::<usize>();
+
+                let mut selected = selection_output.selected_inputs;
+                selected.sort_unstable();
+                for &i in selected.iter().rev() {
+                    pool.swap_remove(i);
+                }
+                match selection_output.change_value {
+                    Some(change_value) => pool.push(OutputGroup {
+                        value: change_value,
+                        weight: config.input_weight,
+                        input_count: 1,
+                        creation_sequence,
+                        ancestors: None,
+                    }),
+                    None => changeless += 1,
+                }
+            }
+        }
+    }
+
+    report.final_utxo_count = pool.len();
+    report.final_balance = pool.iter().map(|output| output.value).sum();
+    if report.payments > 0 {
+        report.average_inputs = total_inputs as f32 / report.payments as f32;
+        report.changeless_rate = changeless as f32 / report.payments as f32;
+    }
+    report
+}
+
+/// Returns the options of a payment of `value` at `feerate`.
+fn payment_options(config: &SimulationConfig, value: u64, feerate: f32) -> CoinSelectionOpt {
+    // Creating the change now and spending it later
+    let change_cost = calculate_fee(config.change_weight, feerate).unwrap_or(0)
+        + calculate_fee(config.input_weight, config.long_term_feerate).unwrap_or(0);
+    CoinSelectionOpt {
+        target_value: value,
+        target_feerate: feerate,
+        long_term_feerate: Some(config.long_term_feerate),
+        min_absolute_fee: 0,
+        base_weight: config.base_weight,
+        change_weight: config.change_weight,
+        change_cost,
+        avg_input_weight: config.input_weight,
+        avg_output_weight: config.change_weight,
+        min_change_value: config.min_change_value,
+        excess_strategy: ExcessStrategy::ToChange,
+        must_spend: Vec::new(),
+        excluded: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        algorithms::{fifo::Fifo, srd::SingleRandomDraw},
+        selectcoin::CoinSelector,
+        simulation::{simulate, Scenario, SimulationConfig, SimulationEvent},
+    };
+
+    const SCENARIO: &str = "\
+# Deposits at a low feerate, then payments as fees rise
+feerate,1.0
+incoming,500000
+incoming,300000
+incoming,120000
+incoming,80000
+outgoing,150000
+outgoing,60000
+feerate,5.0
+outgoing,200000
+incoming,40000
+outgoing,90000
+outgoing,5000000
+";
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario = Scenario::parse(SCENARIO).unwrap();
+        assert_eq!(scenario.events.len(), 12);
+        assert_eq!(scenario.events[0], SimulationEvent::Feerate(1.0));
+        assert_eq!(scenario.events[1], SimulationEvent::Incoming(500_000));
+        assert_eq!(scenario.events[5], SimulationEvent::Outgoing(150_000));
+
+        let error = Scenario::parse("incoming,1000\nrefund,20\n").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.content, "refund,20");
+        assert!(Scenario::parse("outgoing,-20").is_err());
+    }
+
+    #[test]
+    fn test_simulate() {
+        let scenario = Scenario::parse(SCENARIO).unwrap();
+        let config = SimulationConfig::default();
+        let report = simulate(&scenario, &CoinSelector::default(), &config, 42);
+        // The last payment exceeds the balance
+        assert_eq!(report.payments, 4);
+        assert_eq!(report.failed_payments, 1);
+        // Every sat received was either paid, spent in fees or is still in the pool
+        assert_eq!(
+            report.final_balance + report.total_fees,
+            1_040_000 - 150_000 - 60_000 - 200_000 - 90_000
+        );
+        assert!(report.total_fees > 0);
+        assert!(report.average_inputs >= 1.0);
+        assert!((0.0..=1.0).contains(&report.changeless_rate));
+
+        // The same seed replays the same lifetime, with randomized algorithms too
+        let selector = CoinSelector::new().add_algorithm(SingleRandomDraw);
+        assert_eq!(
+            simulate(&scenario, &selector, &config, 7),
+            simulate(&scenario, &selector, &config, 7)
+        );
+
+        // FIFO spends the oldest deposit first, and its change joins the pool
+        let report = simulate(
+            &Scenario::parse("incoming,500000\nincoming,300000\noutgoing,100000").unwrap(),
+            &CoinSelector::new().add_algorithm(Fifo),
+            &config,
+            0,
+        );
+        assert_eq!(report.final_utxo_count, 2);
+        assert_eq!(report.average_inputs, 1.0);
+        assert_eq!(report.changeless_rate, 0.0);
+    }
+}
@@ -1,10 +1,14 @@
+use alloc::{string::String, vec::Vec};
+use core::time::Duration;
+
 /// Represents an input candidate for Coinselection, either as a single UTXO or a group of UTXOs.
 ///
 /// A [`OutputGroup`] can be a single UTXO or a group that should be spent together.
 /// Grouping UTXOs belonging to a single address is privacy preserving than grouping UTXOs belonging to different addresses.
 /// In the UTXO model the output of a transaction is used as the input for the new transaction and hence the name [`OutputGroup`]
 /// The library user must craft this structure correctly, as incorrect representation can lead to incorrect selection results.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OutputGroup {
     /// Total value of the UTXO(s) that this `WeightedValue` represents.
     pub value: u64,
@@ -20,10 +24,30 @@ pub struct OutputGroup {
     /// Set to `None` if FIFO selection is not required. Sequence numbers are arbitrary indices that denote the relative age of a UTXO group among a set of groups.
     /// To denote the oldest UTXO group, assign it a sequence number of `Some(0)`.
     pub creation_sequence: Option<u32>,
+    /// The unconfirmed ancestors of these UTXO(s), `None` once they are confirmed.
+    ///
+    /// When the ancestors pay less than the target feerate, spending this group bumps them (CPFP): the fee needed to
+    /// bring their weight to the target feerate, beyond the fee they paid, is added to the fee of the selection.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ancestors: Option<Ancestors>,
+}
+
+/// The unconfirmed ancestors of an [`OutputGroup`].
+///
+/// They include the transaction creating the UTXO(s) itself, as do the `ancestorsize` and `ancestorfees` reported by
+/// Bitcoin Core. Ancestors shared by several groups are counted in each of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ancestors {
+    /// Total weight of the ancestors.
+    pub weight: u64,
+    /// Total fee paid by the ancestors.
+    pub fee: u64,
 }
 
 /// Options required to compute fees and waste metric.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CoinSelectionOpt {
     /// The value we need to select.
     pub target_value: u64,
@@ -64,10 +88,34 @@ pub struct CoinSelectionOpt {
 
     /// Strategy to use the excess value other than fee and target
     pub excess_strategy: ExcessStrategy,
+
+    /// Indices of the inputs that must be part of the selection, such as the inputs of a transaction being replaced.
+    ///
+    /// Their value and weight are accounted for first, and the algorithms only select from the remaining inputs.
+    /// An index out of range of the inputs fails the selection with `MustSpendOutOfRange`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub must_spend: Vec<usize>,
+
+    /// Indices of the inputs that must not be selected, such as coins locked or reserved by another transaction.
+    ///
+    /// An input that is both must-spend and excluded is treated as must-spend.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub excluded: Vec<usize>,
+}
+
+/// A payment output of the transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recipient {
+    /// The value paid to the recipient.
+    pub value: u64,
+    /// Weight of the output in the transaction.
+    pub weight: u64,
 }
 
 /// Strategy to decide what to do with the excess amount.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExcessStrategy {
     /// Adds the excess amount to the transaction fee. This increases the fee rate
     /// and may lead to faster confirmation, but wastes the excess amount.
@@ -86,11 +134,15 @@ pub enum ExcessStrategy {
 
 /// Error Describing failure of a selection attempt, on any subset of inputs.
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SelectionError {
     InsufficientFunds,
     NoSolutionFound,
     NonPositiveFeeRate,
     AbnormallyHighFeeRate,
+    InsufficientReplacementFee,
+    MustSpendOutOfRange,
+    InputReserved,
 }
 
 /// Measures the efficiency of input selection in satoshis, helping evaluate algorithms based on current and long-term fee rates
@@ -99,16 +151,60 @@ pub enum SelectionError {
 /// In high fee rate environments, selecting fewer inputs reduces transaction fees.
 /// In low fee rate environments, selecting more inputs reduces overall fees.
 /// It compares various selection algorithms to find the most optimized solution, represented by the lowest [WasteMetric] value.
-#[derive(Debug)]
-pub struct WasteMetric(pub u64);
+///
+/// The waste is negative when the target feerate is below the long term feerate and spending the selected inputs now
+/// saves more than the cost of change or excess, so selections that consolidate more inputs rank first.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WasteMetric(pub i64);
 
 /// The result of selection algorithm.
-#[derive(Debug)]
+///
+/// The values are related by `total_value = target_value + fee + change_value`, plus the `excess` when it is
+/// added to the recipient's output.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelectionOutput {
     /// The selected input indices, refers to the indices of the inputs Slice Reference.
     pub selected_inputs: Vec<usize>,
     /// The waste amount, for the above inputs.
     pub waste: WasteMetric,
+    /// Total value of the selected inputs.
+    pub total_value: u64,
+    /// Total weight of the selected inputs.
+    pub total_weight: u64,
+    /// Absolute fee paid by the transaction, including any excess added to the fee and the bump of low feerate ancestors.
+    pub fee: u64,
+    /// Value of the change output, `None` if the selection is changeless.
+    pub change_value: Option<u64>,
+    /// Value left over after paying the target and the required fee in a changeless selection.
+    ///
+    /// It is added to the fee or to the recipient's output according to the [`ExcessStrategy`].
+    pub excess: u64,
+    /// Feerate achieved by the transaction, in sats per weight unit, `0.0` for a transaction of zero weight.
+    pub feerate: f32,
+    /// Name of the algorithm that produced the selection.
+    pub algorithm: String,
+}
+
+/// Outcome of a single algorithm in a [`SelectionReport`].
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlgorithmOutcome {
+    /// Name of the algorithm.
+    pub algorithm: String,
+    /// The selection of the algorithm, or the reason it failed.
+    pub result: Result<SelectionOutput, SelectionError>,
+    /// Time the algorithm took to run, `None` where time can't be measured: without the `std` feature and on wasm32.
+    pub duration: Option<Duration>,
+}
+
+/// Outcome of every algorithm of a selection, in the order the algorithms are registered.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelectionReport {
+    /// One outcome per algorithm.
+    pub outcomes: Vec<AlgorithmOutcome>,
 }
 
 /// EffectiveValue type alias
@@ -116,3 +212,90 @@ pub type EffectiveValue = u64;
 
 /// Weight type alias
 pub type Weight = u64;
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+
+    use crate::types::{
+        CoinSelectionOpt, ExcessStrategy, OutputGroup, Recipient, SelectionError, SelectionOutput,
+        WasteMetric,
+    };
+    use alloc::{string::ToString, vec};
+    use serde::{de::DeserializeOwned, Serialize};
+    use std::fmt::Debug;
+
+    fn assert_round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T) {
+        let json = serde_json::to_string(value).unwrap();
+        let decoded: T = serde_json::from_str(&json).unwrap();
+        assert_eq!(&decoded, value);
+    }
+
+    fn setup_options() -> CoinSelectionOpt {
+        CoinSelectionOpt {
+            target_value: 2500,
+            target_feerate: 0.4, // Simplified feerate
+            long_term_feerate: Some(0.4),
+            min_absolute_fee: 0,
+            base_weight: 10,
+            change_weight: 50,
+            change_cost: 10,
+            avg_input_weight: 20,
+            avg_output_weight: 10,
+            min_change_value: 500,
+            excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![1],
+            excluded: vec![2],
+        }
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        assert_round_trip(&OutputGroup {
+            value: 1000,
+            weight: 100,
+            input_count: 1,
+            creation_sequence: Some(5),
+            ..Default::default()
+        });
+        assert_round_trip(&setup_options());
+        assert_round_trip(&Recipient {
+            value: 1000,
+            weight: 124,
+        });
+        assert_round_trip(&ExcessStrategy::ToRecipient);
+        assert_round_trip(&SelectionError::InsufficientFunds);
+        assert_round_trip(&WasteMetric(-42));
+        assert_round_trip(&SelectionOutput {
+            selected_inputs: vec![0, 2],
+            waste: WasteMetric(12),
+            total_value: 4000,
+            total_weight: 200,
+            fee: 100,
+            change_value: Some(1400),
+            excess: 0,
+            feerate: 0.5,
+            algorithm: "bnb".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_serde_field_names() {
+        let json = serde_json::to_value(setup_options()).unwrap();
+        assert_eq!(json["target_value"], 2500);
+        assert_eq!(json["excess_strategy"], "ToChange");
+        assert_eq!(serde_json::to_value(WasteMetric(-42)).unwrap(), -42);
+        assert_eq!(
+            serde_json::to_value(SelectionError::NoSolutionFound).unwrap(),
+            "NoSolutionFound"
+        );
+
+        // The input constraints can be left out
+        let mut json = json;
+        let object = json.as_object_mut().unwrap();
+        object.remove("must_spend");
+        object.remove("excluded");
+        let options: CoinSelectionOpt = serde_json::from_value(json).unwrap();
+        assert!(options.must_spend.is_empty());
+        assert!(options.excluded.is_empty());
+    }
+}
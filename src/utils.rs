@@ -1,33 +1,37 @@
 use crate::types::{
-    CoinSelectionOpt, EffectiveValue, ExcessStrategy, OutputGroup, SelectionError, Weight,
+    CoinSelectionOpt, EffectiveValue, ExcessStrategy, OutputGroup, SelectionError, SelectionOutput,
+    WasteMetric, Weight,
 };
-use std::{collections::HashSet, fmt};
+use alloc::{collections::BTreeSet, string::ToString, vec::Vec};
+use core::fmt;
 
+/// Returns the waste of a selection, which is negative when spending the inputs now is cheaper than spending them later.
 #[inline]
 pub fn calculate_waste(
     options: &CoinSelectionOpt,
     accumulated_value: u64,
     accumulated_weight: u64,
     estimated_fee: u64,
-) -> u64 {
+) -> i64 {
     // waste =  weight*(target feerate - long term fee rate) + cost of change + excess
     // weight - total weight of selected inputs
     // cost of change - includes the fees paid on this transaction's change output plus the fees that will need to be paid to spend it later. If there is no change output, the cost is 0.
     // excess - refers to the difference between the sum of selected inputs and the amount we need to pay (the sum of output values and fees). There shouldn’t be any excess if there is a change output.
 
-    let mut waste: u64 = 0;
+    // The timing cost is negative when the target feerate is below the long term feerate,
+    // which favours consolidating more inputs while fees are low
+    let mut waste: i64 = 0;
     if let Some(long_term_feerate) = options.long_term_feerate {
-        waste = (accumulated_weight as f32 * (options.target_feerate - long_term_feerate)).ceil()
-            as u64;
+        waste = ceil(accumulated_weight as f32 * (options.target_feerate - long_term_feerate));
     }
     if options.excess_strategy != ExcessStrategy::ToChange {
         // Change is not created if excess strategy is ToFee or ToRecipient. Hence cost of change is added
         waste += accumulated_value
             .saturating_sub(options.target_value)
-            .saturating_sub(estimated_fee);
+            .saturating_sub(estimated_fee) as i64;
     } else {
         // Change is created if excess strategy is set to ToChange. Hence 'excess' should be set to 0
-        waste += options.change_cost;
+        waste += options.change_cost as i64;
     }
     waste
 }
@@ -38,7 +42,7 @@ pub fn calculate_waste(
 /// This slice should be sorted in descending order by the value of each `OutputGroup`, with each value being less than `adjusted_target`.
 pub fn calculate_accumulated_weight(
     smaller_coins: &[(usize, EffectiveValue, Weight)],
-    selected_inputs: &HashSet<usize>,
+    selected_inputs: &BTreeSet<usize>,
 ) -> u64 {
     let mut accumulated_weight: u64 = 0;
     for &(index, _value, weight) in smaller_coins {
@@ -56,13 +60,34 @@ impl fmt::Display for SelectionError {
             SelectionError::AbnormallyHighFeeRate => write!(f, "Abnormally high fee rate"),
             SelectionError::InsufficientFunds => write!(f, "The Inputs funds are insufficient"),
             SelectionError::NoSolutionFound => write!(f, "No solution could be derived"),
+            SelectionError::InsufficientReplacementFee => {
+                write!(f, "The replacement can't pay the fee required by BIP125")
+            }
+            SelectionError::MustSpendOutOfRange => {
+                write!(f, "A must-spend index is out of range of the inputs")
+            }
+            SelectionError::InputReserved => {
+                write!(f, "An input is reserved by another transaction")
+            }
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for SelectionError {}
 
-type Result<T> = std::result::Result<T, SelectionError>;
+type Result<T> = core::result::Result<T, SelectionError>;
+
+/// Rounds `value` up to an integer, as `f32::ceil` which isn't available without `std`.
+#[inline]
+fn ceil(value: f32) -> i64 {
+    let truncated = value as i64;
+    if (truncated as f32) < value {
+        truncated + 1
+    } else {
+        truncated
+    }
+}
 
 #[inline]
 pub fn calculate_fee(weight: u64, rate: f32) -> Result<u64> {
@@ -71,16 +96,257 @@ pub fn calculate_fee(weight: u64, rate: f32) -> Result<u64> {
     } else if rate > 1000.0 {
         Err(SelectionError::AbnormallyHighFeeRate)
     } else {
-        Ok((weight as f32 * rate).ceil() as u64)
+        Ok(ceil(weight as f32 * rate) as u64)
     }
 }
 
-/// Returns the effective value of the `OutputGroup`, which is the actual value minus the estimated fee.
+/// Returns the fee needed to bring the unconfirmed ancestors of the `OutputGroup` to `feerate`, beyond what they paid.
+///
+/// It is zero for confirmed groups and for ancestors already paying at least `feerate`.
+#[inline]
+pub fn ancestor_bump(output: &OutputGroup, feerate: f32) -> Result<u64> {
+    match output.ancestors {
+        Some(ancestors) => {
+            Ok(calculate_fee(ancestors.weight, feerate)?.saturating_sub(ancestors.fee))
+        }
+        None => Ok(0),
+    }
+}
+
+/// Returns the effective value of the `OutputGroup`, which is the actual value minus the estimated fee and the bump of
+/// its ancestors.
 #[inline]
 pub fn effective_value(output: &OutputGroup, feerate: f32) -> Result<u64> {
     Ok(output
         .value
-        .saturating_sub(calculate_fee(output.weight, feerate)?))
+        .saturating_sub(calculate_fee(output.weight, feerate)?)
+        .saturating_sub(ancestor_bump(output, feerate)?))
+}
+
+/// Builds the [`SelectionOutput`] of `selected_inputs`, computing the fee, change and excess of the resulting transaction.
+///
+/// A change output is created only with [`ExcessStrategy::ToChange`], when the value left after paying the target
+/// and the fee of the transaction including the change output is at least `min_change_value`. Otherwise the
+/// transaction is changeless and the excess goes to the fee, or to the recipient with [`ExcessStrategy::ToRecipient`].
+/// The fee includes the [`ancestor_bump`] of the selected inputs. Returns `InsufficientFunds` if the selected inputs
+/// can't pay the target and the fee of the changeless transaction.
+pub fn create_selection_output(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    selected_inputs: Vec<usize>,
+    waste: WasteMetric,
+    algorithm: &str,
+) -> Result<SelectionOutput> {
+    let total_value: u64 = selected_inputs.iter().map(|&i| inputs[i].value).sum();
+    let total_weight: u64 = selected_inputs.iter().map(|&i| inputs[i].weight).sum();
+    let available = total_value.saturating_sub(options.target_value);
+    let mut bump: u64 = 0;
+    for &i in &selected_inputs {
+        bump += ancestor_bump(&inputs[i], options.target_feerate)?;
+    }
+
+    let changeless_weight = options.base_weight + total_weight;
+    let changeless_fee = bump
+        + calculate_fee(changeless_weight, options.target_feerate)?.max(options.min_absolute_fee);
+    if available < changeless_fee {
+        // The inputs can't pay the target and the fee, even without change
+        return Err(SelectionError::InsufficientFunds);
+    }
+
+    let mut change_value = None;
+    let mut tx_weight = changeless_weight;
+    if options.excess_strategy == ExcessStrategy::ToChange {
+        let change_fee = bump
+            + calculate_fee(
+                changeless_weight + options.change_weight,
+                options.target_feerate,
+            )?
+            .max(options.min_absolute_fee);
+        let change = available.saturating_sub(change_fee);
+        if change >= options.min_change_value && change > 0 {
+            change_value = Some(change);
+            tx_weight += options.change_weight;
+        }
+    }
+
+    let (fee, excess) = match change_value {
+        Some(change) => (available - change, 0),
+        None => {
+            let excess = available.saturating_sub(changeless_fee);
+            if options.excess_strategy == ExcessStrategy::ToRecipient {
+                (available - excess, excess)
+            } else {
+                (available, excess)
+            }
+        }
+    };
+
+    Ok(SelectionOutput {
+        selected_inputs,
+        waste,
+        total_value,
+        total_weight,
+        fee,
+        change_value,
+        excess,
+        // A weightless transaction has no meaningful feerate, and dividing by its weight would report NaN or infinity
+        feerate: if tx_weight == 0 {
+            0.0
+        } else {
+            fee as f32 / tx_weight as f32
+        },
+        algorithm: algorithm.to_string(),
+    })
+}
+
+/// Runs `algorithm` while honouring the input constraints of `options`: [`CoinSelectionOpt::must_spend`] and [`CoinSelectionOpt::excluded`].
+///
+/// The must-spend inputs are accounted for first: if they fund the target on their own they are returned as the selection.
+/// Otherwise `algorithm` selects from the remaining inputs that are not excluded, with the target reduced by the effective
+/// value of the must-spend inputs. The indices of the returned [`SelectionOutput`] refer to `inputs`, and `algorithm_name`
+/// is reported for the selection. A must-spend index out of range of `inputs` is reported as `MustSpendOutOfRange`.
+///
+/// Inputs with unconfirmed ancestors below the target feerate are given to `algorithm` with their value reduced by their
+/// [`ancestor_bump`], and the bump is added to the fee and the waste of the selection.
+pub fn select_with_input_constraints<F>(
+    inputs: &[OutputGroup],
+    options: &CoinSelectionOpt,
+    algorithm_name: &str,
+    algorithm: F,
+) -> Result<SelectionOutput>
+where
+    F: FnOnce(&[OutputGroup], &CoinSelectionOpt) -> Result<SelectionOutput>,
+{
+    if options
+        .must_spend
+        .iter()
+        .any(|&index| index >= inputs.len())
+    {
+        return Err(SelectionError::MustSpendOutOfRange);
+    }
+    if inputs.iter().any(|input| input.ancestors.is_some()) {
+        let mut bumps: Vec<u64> = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            bumps.push(ancestor_bump(input, options.target_feerate)?);
+        }
+        if bumps.iter().any(|&bump| bump > 0) {
+            // Spending a bumped input costs as much as spending a confirmed input worth its value minus the bump
+            let discounted_inputs: Vec<OutputGroup> = inputs
+                .iter()
+                .zip(&bumps)
+                .map(|(input, &bump)| OutputGroup {
+                    value: input.value.saturating_sub(bump),
+                    ancestors: None,
+                    ..input.clone()
+                })
+                .collect();
+            let selection_output = select_with_input_constraints(
+                &discounted_inputs,
+                options,
+                algorithm_name,
+                algorithm,
+            )?;
+            let bump: u64 = selection_output
+                .selected_inputs
+                .iter()
+                .map(|&i| bumps[i])
+                .sum();
+            return create_selection_output(
+                inputs,
+                options,
+                selection_output.selected_inputs,
+                WasteMetric(selection_output.waste.0 + bump as i64),
+                &selection_output.algorithm,
+            );
+        }
+    }
+
+    let mut must_spend: Vec<usize> = options.must_spend.clone();
+    must_spend.sort_unstable();
+    must_spend.dedup();
+    // Must-spend inputs are never excluded
+    let mut excluded: Vec<usize> = options
+        .excluded
+        .iter()
+        .copied()
+        .filter(|index| must_spend.binary_search(index).is_err())
+        .collect();
+    if must_spend.is_empty() && excluded.is_empty() {
+        return algorithm(inputs, options);
+    }
+    excluded.sort_unstable();
+    excluded.dedup();
+
+    let mut must_spend_value: u64 = 0;
+    let mut must_spend_weight: u64 = 0;
+    let mut must_spend_effective_value: u64 = 0;
+    for &index in &must_spend {
+        must_spend_value += inputs[index].value;
+        must_spend_weight += inputs[index].weight;
+        must_spend_effective_value += effective_value(&inputs[index], options.target_feerate)?;
+    }
+
+    let required_fee = calculate_fee(
+        options.base_weight + must_spend_weight,
+        options.target_feerate,
+    )?
+    .max(options.min_absolute_fee);
+    if !must_spend.is_empty()
+        && must_spend_value >= options.target_value + options.min_change_value + required_fee
+    {
+        let estimated_fee = calculate_fee(must_spend_weight, options.target_feerate)?;
+        let waste = calculate_waste(options, must_spend_value, must_spend_weight, estimated_fee);
+        return create_selection_output(
+            inputs,
+            options,
+            must_spend,
+            WasteMetric(waste),
+            algorithm_name,
+        );
+    }
+
+    // The remaining inputs, along with their index in `inputs`
+    let (remaining_indices, remaining_inputs): (Vec<usize>, Vec<OutputGroup>) = inputs
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            must_spend.binary_search(index).is_err() && excluded.binary_search(index).is_err()
+        })
+        .map(|(index, input)| (index, input.clone()))
+        .unzip();
+    let remaining_options = CoinSelectionOpt {
+        target_value: options
+            .target_value
+            .saturating_sub(must_spend_effective_value),
+        must_spend: Vec::new(),
+        excluded: Vec::new(),
+        ..options.clone()
+    };
+    let remaining_output = algorithm(&remaining_inputs, &remaining_options)?;
+
+    let mut selected_inputs = must_spend;
+    selected_inputs.extend(
+        remaining_output
+            .selected_inputs
+            .iter()
+            .map(|&index| remaining_indices[index]),
+    );
+    let accumulated_value: u64 = selected_inputs.iter().map(|&i| inputs[i].value).sum();
+    let accumulated_weight: u64 = selected_inputs.iter().map(|&i| inputs[i].weight).sum();
+    let estimated_fee = calculate_fee(accumulated_weight, options.target_feerate)?;
+    let waste = calculate_waste(
+        options,
+        accumulated_value,
+        accumulated_weight,
+        estimated_fee,
+    );
+    create_selection_output(
+        inputs,
+        options,
+        selected_inputs,
+        WasteMetric(waste),
+        &remaining_output.algorithm,
+    )
 }
 
 /// Returns the weights of data in transaction other than the list of inputs that would be selected.
@@ -101,7 +367,8 @@ pub fn calculate_base_weight_btc(output_weight: u64) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError};
+    use crate::types::{Ancestors, CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError};
+    use alloc::vec;
 
     fn setup_options(target_value: u64) -> CoinSelectionOpt {
         CoinSelectionOpt {
@@ -116,6 +383,8 @@ mod tests {
             avg_output_weight: 10,
             min_change_value: 500,
             excess_strategy: ExcessStrategy::ToChange,
+            must_spend: vec![],
+            excluded: vec![],
         }
     }
 
@@ -202,6 +471,7 @@ mod tests {
                     weight: 101,
                     input_count: 1,
                     creation_sequence: None,
+                    ..Default::default()
                 },
                 feerate: 1.0,
                 result: Ok(0),
@@ -213,10 +483,41 @@ mod tests {
                     weight: 99,
                     input_count: 1,
                     creation_sequence: None,
+                    ..Default::default()
                 },
                 feerate: 1.0,
                 result: Ok(1),
             },
+            // Ancestors paying 100 of the 500 needed for their weight at the feerate are bumped by the remaining 400
+            TestVector {
+                output: OutputGroup {
+                    value: 1000,
+                    weight: 100,
+                    input_count: 1,
+                    creation_sequence: None,
+                    ancestors: Some(Ancestors {
+                        weight: 500,
+                        fee: 100,
+                    }),
+                },
+                feerate: 1.0,
+                result: Ok(500),
+            },
+            // Ancestors paying above the feerate aren't bumped
+            TestVector {
+                output: OutputGroup {
+                    value: 1000,
+                    weight: 100,
+                    input_count: 1,
+                    creation_sequence: None,
+                    ancestors: Some(Ancestors {
+                        weight: 500,
+                        fee: 600,
+                    }),
+                },
+                feerate: 1.0,
+                result: Ok(900),
+            },
             // Test negative fee rate return appropriate error
             TestVector {
                 output: OutputGroup {
@@ -224,6 +525,7 @@ mod tests {
                     weight: 99,
                     input_count: 1,
                     creation_sequence: None,
+                    ..Default::default()
                 },
                 feerate: -1.0,
                 result: Err(SelectionError::NonPositiveFeeRate),
@@ -235,6 +537,7 @@ mod tests {
                     weight: 99,
                     input_count: 1,
                     creation_sequence: None,
+                    ..Default::default()
                 },
                 feerate: 2000.0,
                 result: Err(SelectionError::AbnormallyHighFeeRate),
@@ -246,6 +549,7 @@ mod tests {
                     weight: 10,
                     input_count: 1,
                     creation_sequence: None,
+                    ..Default::default()
                 },
                 feerate: 1.0,
                 result: Ok(99_999_999_990),
@@ -266,6 +570,132 @@ mod tests {
         }
     }
 
+    /// Tests the construction of the selection output, which reports the fee, change and excess of the transaction.
+    ///
+    /// Test vectors cover:
+    /// - Change output created (ToChange strategy)
+    /// - Change below the minimum change value, added to the fee
+    /// - Excess added to the fee (ToFee strategy)
+    /// - Excess added to the recipient (ToRecipient strategy)
+    #[test]
+    fn test_create_selection_output() {
+        struct TestVector {
+            options: CoinSelectionOpt,
+            fee: u64,
+            change_value: Option<u64>,
+            excess: u64,
+        }
+
+        // Two inputs totalling 3000 sats and 300 wu. Changeless, the transaction weighs 310 wu and pays 124 sats.
+        // With change it weighs 360 wu and pays 144 sats.
+        let inputs = vec![
+            OutputGroup {
+                value: 1000,
+                weight: 100,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+            OutputGroup {
+                value: 2000,
+                weight: 200,
+                input_count: 1,
+                creation_sequence: None,
+                ..Default::default()
+            },
+        ];
+        let options = setup_options(2000);
+        let test_vectors = [
+            TestVector {
+                options: options.clone(),
+                fee: 144,
+                change_value: Some(856),
+                excess: 0,
+            },
+            TestVector {
+                options: CoinSelectionOpt {
+                    min_change_value: 900,
+                    ..options.clone()
+                },
+                fee: 1000,
+                change_value: None,
+                excess: 876,
+            },
+            TestVector {
+                options: CoinSelectionOpt {
+                    excess_strategy: ExcessStrategy::ToFee,
+                    ..options.clone()
+                },
+                fee: 1000,
+                change_value: None,
+                excess: 876,
+            },
+            TestVector {
+                options: CoinSelectionOpt {
+                    excess_strategy: ExcessStrategy::ToRecipient,
+                    ..options.clone()
+                },
+                fee: 124,
+                change_value: None,
+                excess: 876,
+            },
+        ];
+
+        for vector in test_vectors {
+            let output = create_selection_output(
+                &inputs,
+                &vector.options,
+                vec![0, 1],
+                WasteMetric(0),
+                "test",
+            )
+            .unwrap();
+            assert_eq!(output.total_value, 3000);
+            assert_eq!(output.total_weight, 300);
+            assert_eq!(output.fee, vector.fee);
+            assert_eq!(output.change_value, vector.change_value);
+            assert_eq!(output.excess, vector.excess);
+            assert_eq!(output.algorithm, "test");
+        }
+
+        // Weightless inputs and transaction
+        let weightless_inputs = vec![OutputGroup {
+            weight: 0,
+            ..inputs[0].clone()
+        }];
+        let output = create_selection_output(
+            &weightless_inputs,
+            &CoinSelectionOpt {
+                base_weight: 0,
+                min_absolute_fee: 10,
+                excess_strategy: ExcessStrategy::ToRecipient,
+                ..setup_options(500)
+            },
+            vec![0],
+            WasteMetric(0),
+            "test",
+        )
+        .unwrap();
+        assert_eq!(output.fee, 10);
+        assert_eq!(output.feerate, 0.0);
+
+        // Inputs that can't pay the changeless fee on top of the target, whatever the strategy
+        for excess_strategy in [
+            ExcessStrategy::ToChange,
+            ExcessStrategy::ToFee,
+            ExcessStrategy::ToRecipient,
+        ] {
+            let options = CoinSelectionOpt {
+                excess_strategy,
+                ..setup_options(2900)
+            };
+            assert_eq!(
+                create_selection_output(&inputs, &options, vec![0, 1], WasteMetric(0), "test"),
+                Err(SelectionError::InsufficientFunds)
+            );
+        }
+    }
+
     /// Tests the waste metric calculation which helps optimize coin selection.
     /// Waste represents the cost of creating a change output plus any excess amount
     /// that goes to fees or is added to recipient outputs.
@@ -286,7 +716,7 @@ mod tests {
             accumulated_value: u64,
             accumulated_weight: u64,
             estimated_fee: u64,
-            result: u64,
+            result: i64,
         }
 
         let options = setup_options(100).clone();
@@ -297,13 +727,13 @@ mod tests {
                 accumulated_value: 1000,
                 accumulated_weight: 50,
                 estimated_fee: 20,
-                result: options.change_cost,
+                result: options.change_cost as i64,
             },
             // Test for excess strategy to miners
             TestVector {
                 options: CoinSelectionOpt {
                     excess_strategy: ExcessStrategy::ToFee,
-                    ..options
+                    ..options.clone()
                 },
                 accumulated_value: 1000,
                 accumulated_weight: 50,
@@ -315,13 +745,24 @@ mod tests {
                 options: CoinSelectionOpt {
                     target_value: 1000,
                     excess_strategy: ExcessStrategy::ToFee,
-                    ..options
+                    ..options.clone()
                 },
                 accumulated_value: 200,
                 accumulated_weight: 50,
                 estimated_fee: 20,
                 result: 0,
             },
+            // Test target feerate below the long term feerate, the timing cost is negative
+            TestVector {
+                options: CoinSelectionOpt {
+                    target_feerate: 0.2,
+                    ..options.clone()
+                },
+                accumulated_value: 1000,
+                accumulated_weight: 100,
+                estimated_fee: 20,
+                result: -20 + options.change_cost as i64,
+            },
         ];
 
         for vector in test_vectors {
@@ -0,0 +1,334 @@
+use crate::{
+    algorithms::{
+        bnb::select_coin_bnb, coingrinder::select_coin_coingrinder, fifo::select_coin_fifo,
+        knapsack::select_coin_knapsack, lowestlarger::select_coin_lowestlarger,
+        srd::select_coin_srd,
+    },
+    selectcoin::select_coin,
+    types::{
+        Ancestors, CoinSelectionOpt, ExcessStrategy, OutputGroup, SelectionError, SelectionOutput,
+    },
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// An input candidate as given by JavaScript, see [`OutputGroup`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsOutputGroup {
+    /// Total value of the UTXO(s).
+    pub value: u64,
+    /// Total weight of spending the UTXO(s).
+    pub weight: u64,
+    /// The total number of inputs.
+    pub input_count: usize,
+    /// Relative creation sequence, used only for FIFO selection.
+    #[serde(default)]
+    pub creation_sequence: Option<u32>,
+    /// The unconfirmed ancestors, as a `{ weight, fee }` object, absent once confirmed.
+    #[serde(default)]
+    pub ancestors: Option<Ancestors>,
+}
+
+impl From<JsOutputGroup> for OutputGroup {
+    fn from(group: JsOutputGroup) -> Self {
+        OutputGroup {
+            value: group.value,
+            weight: group.weight,
+            input_count: group.input_count,
+            creation_sequence: group.creation_sequence,
+            ancestors: group.ancestors,
+        }
+    }
+}
+
+/// The selection options as given by JavaScript, see [`CoinSelectionOpt`].
+///
+/// The excess strategy is one of the strings `"ToFee"`, `"ToRecipient"` and `"ToChange"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsCoinSelectionOpt {
+    /// The value to select.
+    pub target_value: u64,
+    /// The target feerate in sats per weight unit.
+    pub target_feerate: f32,
+    /// The long term feerate in sats per weight unit.
+    #[serde(default)]
+    pub long_term_feerate: Option<f32>,
+    /// Lowest possible transaction fee.
+    pub min_absolute_fee: u64,
+    /// Weight of the transaction other than the selected inputs.
+    pub base_weight: u64,
+    /// Weight added by a change output.
+    pub change_weight: u64,
+    /// Cost of creating and later spending a change output.
+    pub change_cost: u64,
+    /// Estimate of the average weight of an input.
+    pub avg_input_weight: u64,
+    /// Estimate of the average weight of an output.
+    pub avg_output_weight: u64,
+    /// Smallest change value worth creating.
+    pub min_change_value: u64,
+    /// Where the excess of the selection goes.
+    pub excess_strategy: ExcessStrategy,
+    /// Indices of the inputs that must be spent.
+    #[serde(default)]
+    pub must_spend: Vec<usize>,
+    /// Indices of the inputs that must not be selected.
+    #[serde(default)]
+    pub excluded: Vec<usize>,
+}
+
+impl From<JsCoinSelectionOpt> for CoinSelectionOpt {
+    fn from(options: JsCoinSelectionOpt) -> Self {
+        CoinSelectionOpt {
+            target_value: options.target_value,
+            target_feerate: options.target_feerate,
+            long_term_feerate: options.long_term_feerate,
+            min_absolute_fee: options.min_absolute_fee,
+            base_weight: options.base_weight,
+            change_weight: options.change_weight,
+            change_cost: options.change_cost,
+            avg_input_weight: options.avg_input_weight,
+            avg_output_weight: options.avg_output_weight,
+            min_change_value: options.min_change_value,
+            excess_strategy: options.excess_strategy,
+            must_spend: options.must_spend,
+            excluded: options.excluded,
+        }
+    }
+}
+
+/// A selection as returned to JavaScript, see [`SelectionOutput`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsSelectionOutput {
+    /// Indices of the selected inputs.
+    pub selected_inputs: Vec<usize>,
+    /// Waste of the selection.
+    pub waste: i64,
+    /// Total value of the selected inputs.
+    pub total_value: u64,
+    /// Total weight of the selected inputs.
+    pub total_weight: u64,
+    /// Fee paid by the transaction.
+    pub fee: u64,
+    /// Value of the change output, if any.
+    pub change_value: Option<u64>,
+    /// Excess left to the fee or the recipient.
+    pub excess: u64,
+    /// Effective feerate of the transaction in sats per weight unit.
+    pub feerate: f32,
+    /// Name of the algorithm which made the selection.
+    pub algorithm: String,
+}
+
+impl From<SelectionOutput> for JsSelectionOutput {
+    fn from(output: SelectionOutput) -> Self {
+        JsSelectionOutput {
+            selected_inputs: output.selected_inputs,
+            waste: output.waste.0,
+            total_value: output.total_value,
+            total_weight: output.total_weight,
+            fee: output.fee,
+            change_value: output.change_value,
+            excess: output.excess,
+            feerate: output.feerate,
+            algorithm: output.algorithm,
+        }
+    }
+}
+
+/// The exception thrown to JavaScript when a selection fails.
+///
+/// `kind` is the name of the [`SelectionError`] variant, or `"InvalidInput"` when the arguments can't be read.
+#[wasm_bindgen(js_name = CoinSelectionError)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsSelectionError {
+    kind: String,
+    message: String,
+}
+
+#[wasm_bindgen(js_class = CoinSelectionError)]
+impl JsSelectionError {
+    /// Kind of the error, such as `"InsufficientFunds"`.
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        self.kind.clone()
+    }
+
+    /// Human readable description of the error.
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl JsSelectionError {
+    fn invalid_input(error: impl core::fmt::Display) -> Self {
+        JsSelectionError {
+            kind: "InvalidInput".to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<SelectionError> for JsSelectionError {
+    fn from(error: SelectionError) -> Self {
+        let kind = match error {
+            SelectionError::NonPositiveFeeRate => "NonPositiveFeeRate",
+            SelectionError::AbnormallyHighFeeRate => "AbnormallyHighFeeRate",
+            SelectionError::InsufficientFunds => "InsufficientFunds",
+            SelectionError::NoSolutionFound => "NoSolutionFound",
+            SelectionError::InsufficientReplacementFee => "InsufficientReplacementFee",
+            SelectionError::MustSpendOutOfRange => "MustSpendOutOfRange",
+            SelectionError::InputReserved => "InputReserved",
+        };
+        JsSelectionError {
+            kind: kind.to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Converts the JavaScript arguments, runs `algorithm` and converts its selection back.
+fn select_js(
+    inputs: JsValue,
+    options: JsValue,
+    algorithm: fn(&[OutputGroup], &CoinSelectionOpt) -> Result<SelectionOutput, SelectionError>,
+) -> Result<JsValue, JsSelectionError> {
+    let inputs: Vec<JsOutputGroup> =
+        serde_wasm_bindgen::from_value(inputs).map_err(JsSelectionError::invalid_input)?;
+    let options: JsCoinSelectionOpt =
+        serde_wasm_bindgen::from_value(options).map_err(JsSelectionError::invalid_input)?;
+    let selection_output = run(inputs, options, algorithm)?;
+    serde_wasm_bindgen::to_value(&selection_output).map_err(JsSelectionError::invalid_input)
+}
+
+/// Runs `algorithm` on the converted arguments, independently of JavaScript values.
+fn run(
+    inputs: Vec<JsOutputGroup>,
+    options: JsCoinSelectionOpt,
+    algorithm: fn(&[OutputGroup], &CoinSelectionOpt) -> Result<SelectionOutput, SelectionError>,
+) -> Result<JsSelectionOutput, JsSelectionError> {
+    let inputs: Vec<OutputGroup> = inputs.into_iter().map(OutputGroup::from).collect();
+    let options = CoinSelectionOpt::from(options);
+    Ok(algorithm(&inputs, &options)?.into())
+}
+
+/// Runs every algorithm and returns the selection with the least waste, see [`select_coin`].
+#[wasm_bindgen(js_name = selectCoin)]
+pub fn select_coin_js(inputs: JsValue, options: JsValue) -> Result<JsValue, JsSelectionError> {
+    select_js(inputs, options, select_coin)
+}
+
+/// Selects with Branch and Bound, see [`select_coin_bnb`].
+#[wasm_bindgen(js_name = selectCoinBnb)]
+pub fn select_coin_bnb_js(inputs: JsValue, options: JsValue) -> Result<JsValue, JsSelectionError> {
+    select_js(inputs, options, select_coin_bnb)
+}
+
+/// Selects with CoinGrinder, see [`select_coin_coingrinder`].
+#[wasm_bindgen(js_name = selectCoinCoingrinder)]
+pub fn select_coin_coingrinder_js(
+    inputs: JsValue,
+    options: JsValue,
+) -> Result<JsValue, JsSelectionError> {
+    select_js(inputs, options, select_coin_coingrinder)
+}
+
+/// Selects the oldest inputs first, see [`select_coin_fifo`].
+#[wasm_bindgen(js_name = selectCoinFifo)]
+pub fn select_coin_fifo_js(inputs: JsValue, options: JsValue) -> Result<JsValue, JsSelectionError> {
+    select_js(inputs, options, select_coin_fifo)
+}
+
+/// Selects with the Knapsack algorithm, see [`select_coin_knapsack`].
+#[wasm_bindgen(js_name = selectCoinKnapsack)]
+pub fn select_coin_knapsack_js(
+    inputs: JsValue,
+    options: JsValue,
+) -> Result<JsValue, JsSelectionError> {
+    select_js(inputs, options, select_coin_knapsack)
+}
+
+/// Selects the lowest larger input, or else the largest ones, see [`select_coin_lowestlarger`].
+#[wasm_bindgen(js_name = selectCoinLowestLarger)]
+pub fn select_coin_lowestlarger_js(
+    inputs: JsValue,
+    options: JsValue,
+) -> Result<JsValue, JsSelectionError> {
+    select_js(inputs, options, select_coin_lowestlarger)
+}
+
+/// Selects with a single random draw, see [`select_coin_srd`].
+#[wasm_bindgen(js_name = selectCoinSrd)]
+pub fn select_coin_srd_js(inputs: JsValue, options: JsValue) -> Result<JsValue, JsSelectionError> {
+    select_js(inputs, options, select_coin_srd)
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        algorithms::fifo::select_coin_fifo,
+        types::ExcessStrategy,
+        wasm::{run, JsCoinSelectionOpt, JsOutputGroup, JsSelectionError},
+    };
+
+    fn setup_inputs() -> Vec<JsOutputGroup> {
+        serde_json::from_str(
+            r#"[
+                {"value": 100000, "weight": 272, "inputCount": 1, "creationSequence": 1},
+                {"value": 200000, "weight": 272, "inputCount": 1, "creationSequence": 0},
+                {"value": 300000, "weight": 272, "inputCount": 1}
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    fn setup_options(target_value: u64) -> JsCoinSelectionOpt {
+        serde_json::from_value(serde_json::json!({
+            "targetValue": target_value,
+            "targetFeerate": 0.5,
+            "longTermFeerate": 0.4,
+            "minAbsoluteFee": 0,
+            "baseWeight": 40,
+            "changeWeight": 124,
+            "changeCost": 100,
+            "avgInputWeight": 272,
+            "avgOutputWeight": 124,
+            "minChangeValue": 500,
+            "excessStrategy": "ToChange"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_js_inputs() {
+        let inputs = setup_inputs();
+        assert_eq!(inputs[0].input_count, 1);
+        assert_eq!(inputs[1].creation_sequence, Some(0));
+        assert_eq!(inputs[2].creation_sequence, None);
+        let options = setup_options(150_000);
+        assert_eq!(options.excess_strategy, ExcessStrategy::ToChange);
+        assert!(options.must_spend.is_empty());
+
+        let selection_output = run(inputs, options, select_coin_fifo).unwrap();
+        assert_eq!(selection_output.selected_inputs, vec![1]);
+        let json = serde_json::to_value(&selection_output).unwrap();
+        assert_eq!(json["selectedInputs"], serde_json::json!([1]));
+        assert!(json["changeValue"].is_u64());
+        assert_eq!(json["algorithm"], "fifo");
+    }
+
+    #[test]
+    fn test_js_errors() {
+        let error = run(setup_inputs(), setup_options(1_000_000), select_coin_fifo).unwrap_err();
+        assert_eq!(error.kind(), "InsufficientFunds");
+        assert_eq!(error.message(), "The Inputs funds are insufficient");
+
+        let error = JsSelectionError::invalid_input("missing field `targetValue`");
+        assert_eq!(error.kind(), "InvalidInput");
+    }
+}
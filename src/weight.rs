@@ -0,0 +1,193 @@
+use alloc::{vec, vec::Vec};
+
+/// Weight of the outpoint and sequence of an input, which don't depend on how it is spent.
+pub const TXIN_BASE_WEIGHT: u64 = (32 + 4 + 4) * 4;
+
+/// Largest size of a standard ECDSA signature: a 71 bytes low-S DER encoding and the sighash flag.
+pub const ECDSA_SIGNATURE_MAX_SIZE: u64 = 72;
+
+/// Largest size of a Schnorr signature: 64 bytes and a non-default sighash flag.
+pub const SCHNORR_SIGNATURE_MAX_SIZE: u64 = 65;
+
+/// Size of a compressed public key.
+pub const COMPRESSED_PUBKEY_SIZE: u64 = 33;
+
+/// Returns the size of the compact size encoding of `n`, which prefixes scripts, witnesses and their elements.
+pub const fn varint_size(n: u64) -> u64 {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x10000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// Returns the size of a data push of `size` bytes in a script.
+const fn push_size(size: u64) -> u64 {
+    match size {
+        0..=75 => 1 + size,
+        76..=0xff => 2 + size,
+        0x100..=0xffff => 3 + size,
+        _ => 5 + size,
+    }
+}
+
+/// Returns the weight of an input with a script sig of `script_sig_size` bytes and witness elements of the given sizes.
+///
+/// Inputs without witness still count the empty witness they have in a segwit transaction.
+pub fn input_weight(script_sig_size: u64, witness_element_sizes: &[u64]) -> u64 {
+    let script_sig_weight = (varint_size(script_sig_size) + script_sig_size) * 4;
+    let witness_weight = varint_size(witness_element_sizes.len() as u64)
+        + witness_element_sizes
+            .iter()
+            .map(|&size| varint_size(size) + size)
+            .sum::<u64>();
+    TXIN_BASE_WEIGHT + script_sig_weight + witness_weight
+}
+
+/// Returns the weight of an input spending a pay to public key hash output with a compressed public key.
+pub fn p2pkh_input_weight() -> u64 {
+    input_weight(
+        push_size(ECDSA_SIGNATURE_MAX_SIZE) + push_size(COMPRESSED_PUBKEY_SIZE),
+        &[],
+    )
+}
+
+/// Returns the weight of an input spending a pay to witness public key hash output nested in pay to script hash.
+pub fn p2sh_p2wpkh_input_weight() -> u64 {
+    // The script sig pushes the 22 bytes witness program
+    input_weight(
+        push_size(22),
+        &[ECDSA_SIGNATURE_MAX_SIZE, COMPRESSED_PUBKEY_SIZE],
+    )
+}
+
+/// Returns the weight of an input spending a pay to witness public key hash output.
+pub fn p2wpkh_input_weight() -> u64 {
+    input_weight(0, &[ECDSA_SIGNATURE_MAX_SIZE, COMPRESSED_PUBKEY_SIZE])
+}
+
+/// Returns the size of a `m`-of-`n` multisig script with compressed public keys.
+pub fn multisig_script_size(m: u64, n: u64) -> u64 {
+    // OP_m <pubkeys> OP_n OP_CHECKMULTISIG, with m and n pushed as single byte opcodes up to 16
+    let number_size = |k: u64| if k <= 16 { 1 } else { push_size(1) };
+    number_size(m) + n * push_size(COMPRESSED_PUBKEY_SIZE) + number_size(n) + 1
+}
+
+/// Returns the weight of an input spending a `m`-of-`n` multisig pay to witness script hash output.
+pub fn p2wsh_multisig_input_weight(m: u64, n: u64) -> u64 {
+    // The witness has the empty element consumed by OP_CHECKMULTISIG, the m signatures and the witness script
+    let mut witness_element_sizes = vec![0];
+    witness_element_sizes.extend((0..m).map(|_| ECDSA_SIGNATURE_MAX_SIZE));
+    witness_element_sizes.push(multisig_script_size(m, n));
+    input_weight(0, &witness_element_sizes)
+}
+
+/// Returns the weight of an input spending a pay to taproot output through the key path.
+pub fn p2tr_key_path_input_weight() -> u64 {
+    input_weight(0, &[SCHNORR_SIGNATURE_MAX_SIZE])
+}
+
+/// Returns the weight of an input spending a pay to taproot output through a script path.
+///
+/// The leaf script of `leaf_script_size` bytes is satisfied with `signature_count` Schnorr signatures, and revealed
+/// with a control block of `control_block_size` bytes, which is 33 bytes plus 32 bytes per level of the script tree.
+pub fn p2tr_script_path_input_weight(
+    leaf_script_size: u64,
+    control_block_size: u64,
+    signature_count: u64,
+) -> u64 {
+    let mut witness_element_sizes: Vec<u64> = (0..signature_count)
+        .map(|_| SCHNORR_SIGNATURE_MAX_SIZE)
+        .collect();
+    witness_element_sizes.push(leaf_script_size);
+    witness_element_sizes.push(control_block_size);
+    input_weight(0, &witness_element_sizes)
+}
+
+/// Returns the weight of an output with a script pubkey of `script_pubkey_size` bytes.
+pub fn output_weight(script_pubkey_size: u64) -> u64 {
+    // The value takes 8 bytes
+    (8 + varint_size(script_pubkey_size) + script_pubkey_size) * 4
+}
+
+/// Standard output script types, to estimate the weight of outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputType {
+    /// Pay to public key hash.
+    P2pkh,
+    /// Pay to script hash.
+    P2sh,
+    /// Pay to witness public key hash.
+    P2wpkh,
+    /// Pay to witness script hash.
+    P2wsh,
+    /// Pay to taproot.
+    P2tr,
+}
+
+impl OutputType {
+    /// Returns the size of the script pubkey of this output type.
+    pub fn script_pubkey_size(&self) -> u64 {
+        match self {
+            OutputType::P2pkh => 25,
+            OutputType::P2sh => 23,
+            OutputType::P2wpkh => 22,
+            OutputType::P2wsh | OutputType::P2tr => 34,
+        }
+    }
+
+    /// Returns the weight of an output of this type.
+    pub fn weight(&self) -> u64 {
+        output_weight(self.script_pubkey_size())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::weight::{
+        multisig_script_size, output_weight, p2pkh_input_weight, p2sh_p2wpkh_input_weight,
+        p2tr_key_path_input_weight, p2tr_script_path_input_weight, p2wpkh_input_weight,
+        p2wsh_multisig_input_weight, varint_size, OutputType,
+    };
+
+    #[test]
+    fn test_input_weights() {
+        assert_eq!(p2pkh_input_weight(), 593);
+        assert_eq!(p2sh_p2wpkh_input_weight(), 364);
+        assert_eq!(p2wpkh_input_weight(), 272);
+        assert_eq!(p2tr_key_path_input_weight(), 231);
+
+        // 2-of-3 multisig: a 105 bytes witness script and two signatures
+        assert_eq!(multisig_script_size(2, 3), 105);
+        assert_eq!(p2wsh_multisig_input_weight(2, 3), 418);
+        // Each additional signature adds its size and length prefix
+        assert_eq!(
+            p2wsh_multisig_input_weight(3, 3) - p2wsh_multisig_input_weight(2, 3),
+            73
+        );
+
+        // A single key leaf script (<pubkey> OP_CHECKSIG) at depth one of the script tree
+        assert_eq!(
+            p2tr_script_path_input_weight(34, 65, 1),
+            164 + 1 + 66 + 35 + 66
+        );
+    }
+
+    #[test]
+    fn test_output_weights() {
+        assert_eq!(OutputType::P2pkh.weight(), 136);
+        assert_eq!(OutputType::P2sh.weight(), 128);
+        assert_eq!(OutputType::P2wpkh.weight(), 124);
+        assert_eq!(OutputType::P2wsh.weight(), 172);
+        assert_eq!(OutputType::P2tr.weight(), 172);
+        assert_eq!(output_weight(0), 36);
+
+        assert_eq!(varint_size(0xfc), 1);
+        assert_eq!(varint_size(0xfd), 3);
+        assert_eq!(varint_size(0x10000), 5);
+        assert_eq!(output_weight(300), (8 + 3 + 300) * 4);
+    }
+}
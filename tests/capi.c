@@ -0,0 +1,64 @@
+/* Selects coins through the C interface, run by tests/capi.rs. */
+#include <stdio.h>
+#include "rust_coinselect.h"
+
+#define CHECK(condition)                                                  \
+    do {                                                                  \
+        if (!(condition)) {                                               \
+            fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #condition); \
+            return 1;                                                     \
+        }                                                                 \
+    } while (0)
+
+static CoinselectOptions setup_options(uint64_t target_value) {
+    CoinselectOptions options = {0};
+    options.target_value = target_value;
+    options.target_feerate = 0.5f;
+    options.long_term_feerate = 0.4f;
+    options.has_long_term_feerate = true;
+    options.base_weight = 40;
+    options.change_weight = 124;
+    options.change_cost = 100;
+    options.avg_input_weight = 272;
+    options.avg_output_weight = 124;
+    options.min_change_value = 500;
+    options.excess_strategy = COINSELECT_EXCESS_STRATEGY_TO_CHANGE;
+    return options;
+}
+
+int main(void) {
+    CoinselectOutputGroup inputs[3] = {
+        {100000, 272, 1, 2, true},
+        {200000, 272, 1, 1, true},
+        {300000, 272, 1, 0, true},
+    };
+    size_t excluded[1] = {2};
+    CoinselectOptions options = setup_options(150000);
+    CoinselectSelection selection;
+
+    /* The oldest input is excluded, FIFO then spends the second oldest */
+    options.excluded = excluded;
+    options.excluded_len = 1;
+    CHECK(coinselect_select_coin_fifo(inputs, 3, &options, &selection) == COINSELECT_STATUS_OK);
+    CHECK(selection.selected_inputs_len == 1);
+    CHECK(selection.selected_inputs[0] == 1);
+    CHECK(selection.change_value > 0);
+    coinselect_selection_free(&selection);
+    CHECK(selection.selected_inputs == NULL);
+
+    /* Every algorithm funds the target */
+    options = setup_options(150000);
+    CHECK(coinselect_select_coin(inputs, 3, &options, &selection) == COINSELECT_STATUS_OK);
+    CHECK(selection.selected_inputs_len > 0);
+    coinselect_selection_free(&selection);
+
+    /* Errors are reported as status codes */
+    options = setup_options(1000000);
+    CHECK(coinselect_select_coin(inputs, 3, &options, &selection) == COINSELECT_STATUS_INSUFFICIENT_FUNDS);
+    CHECK(COINSELECT_STATUS_INSUFFICIENT_FUNDS == 3);
+    CHECK(coinselect_select_coin(inputs, 3, NULL, &selection) == COINSELECT_STATUS_INVALID_ARGUMENT);
+    options = setup_options(150000);
+    options.excess_strategy = 3;
+    CHECK(coinselect_select_coin(inputs, 3, &options, &selection) == COINSELECT_STATUS_INVALID_ARGUMENT);
+    return 0;
+}
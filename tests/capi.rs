@@ -0,0 +1,61 @@
+//! Checks the generated C header and runs the C test program against the crate built as a dynamic library.
+#![cfg(feature = "capi")]
+
+use std::{env, fs, path::Path, process::Command};
+
+fn crate_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn test_header_up_to_date() {
+    let config = cbindgen::Config::from_file(crate_dir().join("cbindgen.toml")).unwrap();
+    let mut expected = Vec::new();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir().join("src/capi.rs"))
+        .generate()
+        .unwrap()
+        .write(&mut expected);
+    let header = fs::read(crate_dir().join("include/rust_coinselect.h")).unwrap();
+    assert!(
+        header == expected,
+        "include/rust_coinselect.h is out of date, regenerate it with \
+         `cbindgen --config cbindgen.toml --output include/rust_coinselect.h`"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_c_program() {
+    // A separate target directory avoids waiting on the lock of the one building this test
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("capi");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .current_dir(crate_dir())
+        .args(["rustc", "--lib", "--features", "capi"])
+        .args(["--crate-type", "cdylib", "--target-dir"])
+        .arg(&target_dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "the dynamic library doesn't build");
+
+    let lib_dir = target_dir.join("debug");
+    let program = target_dir.join("capi_test");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg(crate_dir().join("tests/capi.c"))
+        .arg("-I")
+        .arg(crate_dir().join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lrust_coinselect")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .unwrap();
+    assert!(status.success(), "the C test program doesn't compile");
+    let status = Command::new(&program).status().unwrap();
+    assert!(status.success(), "the C test program failed");
+}